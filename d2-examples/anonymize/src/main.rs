@@ -0,0 +1,102 @@
+use source2_demo::prelude::*;
+use std::hash::{DefaultHasher, Hash, Hasher};
+
+const IDENTITY_FIELDS: [&str; 3] = ["m_iszPlayerName", "m_iPlayerSteamID", "m_steamID"];
+
+/// Hashes entity events and property changes, identity fields separately.
+#[derive(Default)]
+struct Digest {
+    state: DefaultHasher,
+    identity: DefaultHasher,
+    changes: usize,
+    identity_changes: usize,
+}
+
+impl Observer for Digest {
+    fn on_tick_end(&mut self, ctx: &Context) -> ObserverResult {
+        ctx.tick().hash(&mut self.state);
+        Ok(())
+    }
+
+    fn on_entity(&mut self, ctx: &Context, event: EntityEvents, entity: &Entity) -> ObserverResult {
+        (
+            ctx.tick(),
+            entity.index(),
+            format!("{event:?}"),
+            entity.class().name(),
+        )
+            .hash(&mut self.state);
+        Ok(())
+    }
+
+    fn on_property_change(
+        &mut self,
+        ctx: &Context,
+        entity: &Entity,
+        change: &PropertyChange,
+    ) -> ObserverResult {
+        let name = change.name(entity);
+        let hasher = if IDENTITY_FIELDS.iter().any(|field| name.ends_with(field)) {
+            self.identity_changes += 1;
+            &mut self.identity
+        } else {
+            self.changes += 1;
+            &mut self.state
+        };
+        (
+            ctx.tick(),
+            entity.index(),
+            name,
+            format!("{:?}", change.value()),
+        )
+            .hash(hasher);
+        Ok(())
+    }
+
+    fn property_tracking(&self) -> PropertyTracking {
+        PropertyTracking::Paths
+    }
+}
+
+fn digest(replay: &[u8]) -> anyhow::Result<Digest> {
+    let mut parser = Parser::new(replay)?;
+    let digest = parser.add_observer(Digest::default());
+    parser.run_to_end()?;
+    drop(parser);
+    Ok(digest.into_inner().expect("parser is dropped"))
+}
+
+fn main() -> anyhow::Result<()> {
+    let args = std::env::args().collect::<Vec<_>>();
+    let Some(filepath) = args.get(1) else {
+        eprintln!("Usage: {} <demofile> [output]", args[0]);
+        return Ok(());
+    };
+
+    let replay = unsafe { memmap2::Mmap::map(&std::fs::File::open(filepath)?)? };
+    let anonymized = Anonymizer::new(&replay)?.run()?;
+
+    // Anonymized replay must parse identically apart from identity fields
+    let original = digest(&replay)?;
+    let result = digest(&anonymized)?;
+    anyhow::ensure!(
+        original.changes == result.changes
+            && original.identity_changes == result.identity_changes
+            && original.state.finish() == result.state.finish(),
+        "anonymized replay differs outside of identity fields"
+    );
+    anyhow::ensure!(
+        original.identity_changes == 0 || original.identity.finish() != result.identity.finish(),
+        "identity fields were not anonymized"
+    );
+    println!(
+        "{} property changes identical, {} identity values rewritten",
+        original.changes, original.identity_changes
+    );
+
+    if let Some(output) = args.get(2) {
+        std::fs::write(output, &anonymized)?;
+    }
+
+    Ok(())
+}
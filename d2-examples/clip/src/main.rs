@@ -0,0 +1,60 @@
+use source2_demo::prelude::*;
+
+#[derive(Default)]
+struct Ticks {
+    first: Option<u32>,
+    last: u32,
+    count: usize,
+}
+
+impl Observer for Ticks {
+    fn on_tick_start(&mut self, ctx: &Context) -> ObserverResult {
+        self.first.get_or_insert(ctx.tick());
+        self.last = ctx.tick();
+        self.count += 1;
+        Ok(())
+    }
+}
+
+fn main() -> anyhow::Result<()> {
+    let args = std::env::args().collect::<Vec<_>>();
+    let (Some(filepath), Some(start_tick), Some(end_tick)) =
+        (args.get(1), args.get(2), args.get(3))
+    else {
+        eprintln!(
+            "Usage: {} <demofile> <start tick> <end tick> [output]",
+            args[0]
+        );
+        return Ok(());
+    };
+    let start_tick = start_tick.parse::<u32>()?;
+    let end_tick = end_tick.parse::<u32>()?;
+
+    let replay = unsafe { memmap2::Mmap::map(&std::fs::File::open(filepath)?)? };
+    let clip = DemoWriter::new(&replay)?.clip(start_tick, end_tick)?;
+
+    // Clip must parse as a regular replay covering the requested ticks
+    let mut parser = Parser::new(&clip)?;
+    let ticks = parser.add_observer(Ticks::default());
+    parser.run_to_end()?;
+    drop(parser);
+
+    let ticks = ticks.borrow();
+    let first = ticks.first.unwrap_or_default();
+    anyhow::ensure!(
+        first == start_tick && ticks.last <= end_tick,
+        "clip covers ticks {first}..={}, expected {start_tick}..={end_tick}",
+        ticks.last
+    );
+    println!(
+        "Clip {start_tick}..={end_tick}: {} bytes, {} ticks",
+        clip.len(),
+        ticks.count
+    );
+
+    if let Some(output) = args.get(4) {
+        std::fs::write(output, &clip)?;
+    }
+
+    Ok(())
+}
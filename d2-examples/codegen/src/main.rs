@@ -0,0 +1,19 @@
+use source2_demo::CodeGenerator;
+
+fn main() -> anyhow::Result<()> {
+    let args = std::env::args().collect::<Vec<_>>();
+    let Some(filepath) = args.get(1) else {
+        eprintln!("Usage: {} <demofile> [class pattern]...", args[0]);
+        return Ok(());
+    };
+
+    let patterns = match args[2..].is_empty() {
+        true => vec!["*"],
+        false => args[2..].iter().map(String::as_str).collect(),
+    };
+
+    let replay = unsafe { memmap2::Mmap::map(&std::fs::File::open(filepath)?)? };
+    print!("{}", CodeGenerator::new(&replay)?.generate(&patterns));
+
+    Ok(())
+}
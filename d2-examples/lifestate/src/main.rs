@@ -1,16 +1,13 @@
 use source2_demo::prelude::*;
-use std::collections::HashMap;
 use std::io::Write;
 
 struct LifeState {
-    current_life_state: HashMap<u32, i32>,
     output: Box<dyn Write>,
 }
 
 impl Default for LifeState {
     fn default() -> Self {
         LifeState {
-            current_life_state: HashMap::default(),
             output: Box::new(std::io::stdout()),
         }
     }
@@ -18,40 +15,33 @@ impl Default for LifeState {
 
 #[observer]
 impl LifeState {
-    #[on_entity]
-    fn handle_entities(&mut self, ctx: &Context, entity: &Entity) -> ObserverResult {
-        if let Ok(life_state) = entity.get_property_by_name("m_lifeState") {
-            let new_state: i32 = life_state.try_into()?;
-            let old_state: i32 = *self.current_life_state.get(&entity.index()).unwrap_or(&2);
-            if old_state != new_state {
-                match new_state {
-                    0 => {
-                        writeln!(
-                            self.output,
-                            "{:06}: {} at index {} has spawned",
-                            ctx.tick(),
-                            entity.class().name(),
-                            entity.index()
-                        )?;
-                        self.current_life_state.insert(entity.index(), new_state);
-                    }
-                    1 => {
-                        writeln!(
-                            self.output,
-                            "{:06}: {} at index {} has died",
-                            ctx.tick(),
-                            entity.class().name(),
-                            entity.index()
-                        )?;
-                        self.current_life_state.insert(entity.index(), new_state);
-                    }
-                    2 => {
-                        self.current_life_state.remove(&entity.index());
-                    }
-                    _ => unreachable!(),
-                }
-            }
+    #[on_property_change("m_lifeState")]
+    fn handle_life_state(
+        &mut self,
+        ctx: &Context,
+        entity: &Entity,
+        old: Option<&FieldValue>,
+        new: &FieldValue,
+    ) -> ObserverResult {
+        if old == Some(new) {
+            return Ok(());
         }
+
+        let new: i32 = new.try_into()?;
+        let event = match new {
+            0 => "spawned",
+            1 => "died",
+            _ => return Ok(()),
+        };
+        writeln!(
+            self.output,
+            "{:06}: {} at index {} has {}",
+            ctx.tick(),
+            entity.class().name(),
+            entity.index(),
+            event
+        )?;
+
         Ok(())
     }
 }
@@ -104,9 +104,8 @@ impl Positions {
             self.initialized = true;
         }
 
-        // Dota2 は 30 tick/秒
         let tick = ctx.tick();
-        let time_s = tick as f32 / 30.0;
+        let time_s = tick as f32 * ctx.tick_interval();
 
         let player_resource = ctx
             .entities()
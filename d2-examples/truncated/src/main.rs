@@ -0,0 +1,54 @@
+use source2_demo::prelude::*;
+
+#[derive(Default)]
+struct Stops {
+    last_tick: u32,
+    stops: usize,
+}
+
+impl Observer for Stops {
+    fn on_tick_start(&mut self, ctx: &Context) -> ObserverResult {
+        self.last_tick = ctx.tick();
+        Ok(())
+    }
+
+    fn on_stop(&mut self, _ctx: &Context) -> ObserverResult {
+        self.stops += 1;
+        Ok(())
+    }
+}
+
+fn main() -> anyhow::Result<()> {
+    let args = std::env::args().collect::<Vec<_>>();
+    let Some(filepath) = args.get(1) else {
+        eprintln!("Usage: {} <demofile> [percent to keep]", args[0]);
+        return Ok(());
+    };
+    let percent = args.get(2).map_or(Ok(50), |x| x.parse::<usize>())?;
+
+    let replay = std::fs::read(filepath)?;
+    let truncated = &replay[..replay.len() * percent / 100];
+
+    // Truncated replay has no file info, parser must still open it and stop
+    // at the last good tick in lenient mode
+    let mut parser = Parser::new(truncated)?;
+    parser.lenient(true);
+    let stops = parser.add_observer(Stops::default());
+    parser.run_to_end()?;
+
+    let corruption = parser
+        .corruption()
+        .map(|e| e.to_string())
+        .ok_or_else(|| anyhow::anyhow!("truncated replay parsed without corruption"))?;
+    drop(parser);
+
+    let stops = stops.borrow();
+    anyhow::ensure!(stops.stops == 1, "on_stop fired {} times", stops.stops);
+    println!(
+        "Truncated to {} bytes, stopped at tick {}: {corruption}",
+        truncated.len(),
+        stops.last_tick
+    );
+
+    Ok(())
+}
@@ -26,8 +26,6 @@ impl WardsObserver for WardEvents {
     }
 }
 
-impl Observer for WardEvents {}
-
 fn main() -> anyhow::Result<()> {
     let args = std::env::args().collect::<Vec<_>>();
     let Some(filepath) = args.get(1) else {
@@ -38,9 +36,9 @@ fn main() -> anyhow::Result<()> {
     let replay = unsafe { memmap2::Mmap::map(&std::fs::File::open(filepath)?)? };
     let mut parser = Parser::new(&replay)?;
 
-    let wards = parser.register_observer::<Wards>();
-    let ward_events = parser.register_observer::<WardEvents>();
-    wards.borrow_mut().register_observer(ward_events);
+    let mut wards = Wards::default();
+    wards.register_observer(WardEvents);
+    parser.add_observer(wards);
 
     let start = std::time::Instant::now();
     parser.run_to_end()?;
@@ -52,8 +52,8 @@ pub struct Wards {
 }
 
 impl Wards {
-    pub fn register_observer<T: WardsObserver + 'static>(&mut self, obs: Rc<RefCell<T>>) {
-        self.observers.push(obs as Rc<RefCell<dyn WardsObserver>>)
+    pub fn register_observer<T: WardsObserver + 'static>(&mut self, obs: T) {
+        self.observers.push(Rc::new(RefCell::new(obs)))
     }
 }
 
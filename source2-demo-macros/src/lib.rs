@@ -3,7 +3,8 @@ mod protobuf_map;
 use crate::protobuf_map::get_enum_from_struct;
 use proc_macro::TokenStream;
 use quote::{quote, ToTokens};
-use syn::{parse_macro_input, FnArg, ItemImpl, Type};
+use syn::punctuated::Punctuated;
+use syn::{parse_macro_input, FnArg, ItemImpl, Token, Type};
 
 #[proc_macro_attribute]
 pub fn observer(_attr: TokenStream, item: TokenStream) -> TokenStream {
@@ -28,9 +29,12 @@ pub fn observer(_attr: TokenStream, item: TokenStream) -> TokenStream {
     let mut on_tick_start_body = quote!();
     let mut on_tick_end_body = quote!();
     let mut on_entity_body = quote!();
+    let mut on_property_change_body = quote!();
+    let mut property_tracking = false;
     let mut on_game_event_body = quote!();
     let mut on_string_table_body = quote!();
     let mut on_stop_body = quote!();
+    let mut on_unknown_message_body = quote!();
 
     for item in &input.items {
         if let syn::ImplItem::Fn(method) = item {
@@ -38,8 +42,9 @@ pub fn observer(_attr: TokenStream, item: TokenStream) -> TokenStream {
                 let method_name = method.sig.ident.clone();
                 let mut args = vec![];
 
-                let (arg_type, _) = get_arg_type(method, 1);
-                if arg_type.to_token_stream().to_string() == "Context" {
+                if method.sig.inputs.len() > 1
+                    && get_arg_type(method, 1).0.to_token_stream().to_string() == "Context"
+                {
                     args.push(quote! { ctx })
                 }
 
@@ -51,11 +56,41 @@ pub fn observer(_attr: TokenStream, item: TokenStream) -> TokenStream {
                         "on_tick_end" => on_tick_end_body.extend(quote! {
                             self.#method_name(#(#args),*)?;
                         }),
+                        "on_tick_interval" => {
+                            let ticks = attr
+                                .parse_args::<syn::LitInt>()
+                                .expect("Expected interval in ticks, e.g. #[on_tick_interval(30)]");
+                            on_tick_end_body.extend(quote! {
+                                if ctx.is_tick_interval(#ticks) {
+                                    self.#method_name(#(#args),*)?;
+                                }
+                            })
+                        }
+                        "on_interval" => {
+                            let seconds = attr
+                                .parse_args::<syn::MetaNameValue>()
+                                .ok()
+                                .filter(|x| x.path.is_ident("seconds"))
+                                .map(|x| x.value)
+                                .expect("Expected interval in seconds, e.g. #[on_interval(seconds = 1.0)]");
+                            on_tick_end_body.extend(quote! {
+                                if ctx.is_time_interval((#seconds) as f32) {
+                                    self.#method_name(#(#args),*)?;
+                                }
+                            })
+                        }
                         "on_stop" => {
                             on_stop_body.extend(quote! {
                                 self.#method_name(#(#args),*)?;
                             });
                         }
+                        "on_unknown_message" => {
+                            args.push(quote! { msg_type });
+                            args.push(quote! { msg });
+                            on_unknown_message_body.extend(quote! {
+                                self.#method_name(#(#args),*)?;
+                            });
+                        }
                         #[cfg(feature = "dota")]
                         "on_combat_log" => {
                             args.push(quote! { cle });
@@ -77,9 +112,15 @@ pub fn observer(_attr: TokenStream, item: TokenStream) -> TokenStream {
 
                             args.push(quote! { entity });
 
-                            on_entity_body.extend(if let Ok(entity_class) = attr.parse_args::<syn::LitStr>() {
+                            if method.sig.inputs.len() > args.len() + 1 {
+                                args.push(quote! { ctx.property_changes() });
+                                property_tracking = true;
+                            }
+
+                            on_entity_body.extend(if let Ok(classes) = attr.parse_args_with(Punctuated::<syn::Expr, Token![,]>::parse_terminated) {
+                                let class_condition = class_condition(&classes.into_iter().collect::<Vec<_>>());
                                 quote! {
-                                    if entity.class().name() == #entity_class {
+                                    if #class_condition {
                                         self.#method_name(#(#args),*)?;
                                     }
                                 }
@@ -89,6 +130,39 @@ pub fn observer(_attr: TokenStream, item: TokenStream) -> TokenStream {
                                 }
                             });
                         }
+                        "on_property_change" => {
+                            let filter = attr
+                                .parse_args_with(Punctuated::<syn::Expr, Token![,]>::parse_terminated)
+                                .ok()
+                                .filter(|filter| !filter.is_empty())
+                                .expect("Expected class and property names, e.g. #[on_property_change(\"CDOTA_Unit_Hero_*\", \"m_lifeState\")]")
+                                .into_iter()
+                                .collect::<Vec<_>>();
+                            let (property, classes) = filter.split_last().unwrap();
+                            let property_condition = quote! {
+                                {
+                                    static PROPERTY: PropertyFilter = PropertyFilter::new(#property);
+                                    PROPERTY.matches(entity, change)
+                                }
+                            };
+                            let condition = if classes.is_empty() {
+                                property_condition
+                            } else {
+                                let class_condition = class_condition(classes);
+                                quote! { #class_condition && #property_condition }
+                            };
+
+                            args.push(quote! { entity });
+                            args.push(quote! { change.previous() });
+                            args.push(quote! { change.value() });
+
+                            on_property_change_body.extend(quote! {
+                                if #condition {
+                                    self.#method_name(#(#args),*)?;
+                                }
+                            });
+                            property_tracking = true;
+                        }
                         "on_game_event" => {
                             args.push(quote! { ge });
                             on_game_event_body.extend(if let Ok(event_name) = attr.parse_args::<syn::LitStr>() {
@@ -165,6 +239,13 @@ pub fn observer(_attr: TokenStream, item: TokenStream) -> TokenStream {
         }
     }
 
+    let property_tracking = if property_tracking {
+        quote! { PropertyTracking::Values }
+    } else {
+        quote! { PropertyTracking::Disabled }
+    };
+
+    #[allow(unused_mut)]
     let mut obs_body = quote! {
         fn on_base_user_message(
             &mut self,
@@ -242,6 +323,20 @@ pub fn observer(_attr: TokenStream, item: TokenStream) -> TokenStream {
             Ok(())
         }
 
+        fn on_property_change(
+            &mut self,
+            ctx: &Context,
+            entity: &Entity,
+            change: &PropertyChange,
+        ) -> ObserverResult {
+            #on_property_change_body
+            Ok(())
+        }
+
+        fn property_tracking(&self) -> PropertyTracking {
+            #property_tracking
+        }
+
         fn on_game_event(
             &mut self,
             ctx: &Context,
@@ -268,6 +363,16 @@ pub fn observer(_attr: TokenStream, item: TokenStream) -> TokenStream {
             #on_stop_body
             Ok(())
         }
+
+        fn on_unknown_message(
+            &mut self,
+            ctx: &Context,
+            msg_type: i32,
+            msg: &[u8],
+        ) -> ObserverResult {
+            #on_unknown_message_body
+            Ok(())
+        }
     };
 
     #[cfg(feature = "dota")]
@@ -326,6 +431,49 @@ pub fn observer(_attr: TokenStream, item: TokenStream) -> TokenStream {
     TokenStream::from(ret)
 }
 
+/// Condition matching `entity` class with glob and regex patterns. Single
+/// name without wildcards is compared directly, other patterns are checked by
+/// `ClassFilter` which caches result per class.
+fn class_condition(patterns: &[syn::Expr]) -> proc_macro2::TokenStream {
+    if let [syn::Expr::Lit(syn::ExprLit {
+        lit: syn::Lit::Str(name),
+        ..
+    })] = patterns
+    {
+        if !name.value().contains(['*', '?']) {
+            return quote! { entity.class().name() == #name };
+        }
+    }
+
+    let patterns = patterns.iter().map(|pattern| match pattern {
+        syn::Expr::Lit(syn::ExprLit {
+            lit: syn::Lit::Str(glob),
+            ..
+        }) => quote! { ClassPattern::Glob(#glob) },
+        syn::Expr::Assign(assign) if assign.left.to_token_stream().to_string() == "regex" => {
+            let syn::Expr::Lit(syn::ExprLit {
+                lit: syn::Lit::Str(regex),
+                ..
+            }) = assign.right.as_ref()
+            else {
+                panic!("Expected regex string, e.g. regex = \"^CDOTA_BaseNPC_\"")
+            };
+            if let Err(e) = regex_syntax::parse(&regex.value()) {
+                panic!("Invalid class regex \"{}\": {}", regex.value(), e)
+            }
+            quote! { ClassPattern::Regex(#regex) }
+        }
+        _ => panic!("Expected class pattern, e.g. \"CDOTA_Unit_Hero_*\" or regex = \"^CDOTA_BaseNPC_\""),
+    });
+
+    quote! {
+        {
+            static FILTER: ClassFilter = ClassFilter::new(&[#(#patterns),*]);
+            FILTER.matches(entity.class())
+        }
+    }
+}
+
 fn get_arg_type(method: &syn::ImplItemFn, n: usize) -> (Type, bool) {
     if let Some(FnArg::Typed(pat_type)) = method.sig.inputs.iter().nth(n) {
         if let Type::Reference(x) = pat_type.ty.as_ref() {
@@ -404,6 +552,44 @@ pub fn on_tick_end(_attr: TokenStream, item: TokenStream) -> TokenStream {
     item
 }
 
+/// A method wrapped with `#[on_tick_interval(n)]` macro is called at the end of tick once every `n` ticks.
+///
+/// # Examples
+///
+/// ```no_compile
+/// #[on_tick_interval(30)]
+/// fn sample(&mut self, ctx: &Context) -> ObserverResult {
+///    Ok(())
+/// }
+/// ```
+#[proc_macro_attribute]
+pub fn on_tick_interval(_attr: TokenStream, item: TokenStream) -> TokenStream {
+    item
+}
+
+/// A method wrapped with `#[on_interval(seconds = ..)]` macro is called at the end of tick once every given
+/// number of seconds of replay time, based on the replay's tick interval.
+///
+/// # Examples
+///
+/// ```no_compile
+/// #[on_interval(seconds = 1.0)]
+/// fn sample(&mut self, ctx: &Context) -> ObserverResult {
+///    Ok(())
+/// }
+/// ```
+///
+/// ```no_compile
+/// #[on_interval(seconds = 0.5)]
+/// fn sample(&mut self) -> ObserverResult {
+///    Ok(())
+/// }
+/// ```
+#[proc_macro_attribute]
+pub fn on_interval(_attr: TokenStream, item: TokenStream) -> TokenStream {
+    item
+}
+
 /// A method wrapped with `#[on_entity]` macro is called whenever an entity is created, updated or deleted.
 ///
 /// # Examples
@@ -423,16 +609,61 @@ pub fn on_tick_end(_attr: TokenStream, item: TokenStream) -> TokenStream {
 /// ```
 ///
 /// ```no_compile
+/// // Glob patterns, regular expressions and lists of them, checked once per class
+/// #[on_entity("CDOTA_Unit_Hero_*", regex = "^CDOTA_BaseNPC_(Creep|Tower)")]
+/// fn entity(&mut self, entity: &Entity) -> ObserverResult {
+///    Ok(())
+/// }
+/// ```
+///
+/// ```no_compile
 /// #[on_entity]
 /// fn entity(&mut self, entity: &Entity) -> ObserverResult {
 ///    Ok(())
 /// }
 /// ```
+///
+/// ```no_compile
+/// #[on_entity] // Properties written by this event, see Context::property_changes
+/// fn entity(&mut self, entity: &Entity, changes: &[PropertyChange]) -> ObserverResult {
+///    Ok(())
+/// }
+/// ```
 #[proc_macro_attribute]
 pub fn on_entity(_attr: TokenStream, item: TokenStream) -> TokenStream {
     item
 }
 
+/// A method wrapped with `#[on_property_change]` macro is called when property is written by entity creation or update.
+/// Last argument is property name, preceding ones are class patterns as in `#[on_entity]` and can be omitted to match all classes. Method gets entity,
+/// previous value (`None` if property wasn't set) and new value.
+///
+/// # Examples
+///
+/// ```no_compile
+/// #[on_property_change("CDOTA_Unit_Hero_*", "m_lifeState")]
+/// fn life_state(
+///     &mut self,
+///     ctx: &Context,
+///     entity: &Entity,
+///     old: Option<&FieldValue>,
+///     new: &FieldValue,
+/// ) -> ObserverResult {
+///    Ok(())
+/// }
+/// ```
+///
+/// ```no_compile
+/// #[on_property_change("m_iTeamNum")] // Will be called for all classes
+/// fn team(&mut self, entity: &Entity, old: Option<&FieldValue>, new: &FieldValue) -> ObserverResult {
+///    Ok(())
+/// }
+/// ```
+#[proc_macro_attribute]
+pub fn on_property_change(_attr: TokenStream, item: TokenStream) -> TokenStream {
+    item
+}
+
 /// A method wrapped with `#[on_game_event]` macro is called whenever CSvcMsgGameEvent appears in replay.
 ///
 /// # Examples
@@ -512,6 +743,28 @@ pub fn on_stop(_attr: TokenStream, item: TokenStream) -> TokenStream {
     item
 }
 
+/// A method wrapped with `#[on_unknown_message]` macro is called whenever packet message with unknown id appears in replay.
+///
+/// # Examples
+///
+/// ```no_compile
+/// #[on_unknown_message]
+/// fn unknown(&mut self, ctx: &Context, msg_type: i32, msg: &[u8]) -> ObserverResult {
+///    Ok(())
+/// }
+/// ```
+///
+/// ```no_compile
+/// #[on_unknown_message]
+/// fn unknown(&mut self, msg_type: i32, msg: &[u8]) -> ObserverResult {
+///    Ok(())
+/// }
+/// ```
+#[proc_macro_attribute]
+pub fn on_unknown_message(_attr: TokenStream, item: TokenStream) -> TokenStream {
+    item
+}
+
 /// A method wrapped with `#[on_combat_log]` macro is called whenever CMsgDotaCombatLogEntry appears in replay.
 ///
 /// # Examples
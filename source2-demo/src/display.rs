@@ -1,7 +1,7 @@
 use crate::entity::field::*;
 use crate::entity::*;
 use crate::event::*;
-use crate::parser::Context;
+use crate::parser::{Context, ParserStats, ReplayMessage};
 use crate::string_table::*;
 use prettytable::{row, Table};
 use std::fmt::{Display, Formatter};
@@ -86,6 +86,60 @@ impl Display for Context {
     }
 }
 
+impl Display for ReplayMessage {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ReplayMessage::Demo(msg) => write!(f, "{:?}", msg),
+            ReplayMessage::Net(msg) => write!(f, "{:?}", msg),
+            ReplayMessage::Svc(msg) => write!(f, "{:?}", msg),
+            ReplayMessage::BaseUser(msg) => write!(f, "{:?}", msg),
+            ReplayMessage::BaseGameEvent(msg) => write!(f, "{:?}", msg),
+            #[cfg(feature = "dota")]
+            ReplayMessage::DotaUser(msg) => write!(f, "{:?}", msg),
+            #[cfg(feature = "deadlock")]
+            ReplayMessage::CitadelUser(msg) => write!(f, "{:?}", msg),
+            #[cfg(feature = "deadlock")]
+            ReplayMessage::CitadelGameEvent(msg) => write!(f, "{:?}", msg),
+            ReplayMessage::Unknown(msg) => write!(f, "Unknown({})", msg),
+        }
+    }
+}
+
+impl Display for ParserStats {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        let mut messages = self.messages().collect::<Vec<_>>();
+        messages.sort_by_key(|(_, stats)| std::cmp::Reverse(stats.time));
+
+        let mut table = Table::new();
+        table.add_row(row!["message", "count", "bytes", "time"]);
+        for (msg_type, stats) in messages {
+            table.add_row(row![
+                msg_type,
+                stats.count,
+                stats.bytes,
+                format!("{:?}", stats.time)
+            ]);
+        }
+        writeln!(f, "{}", table)?;
+
+        let mut classes = self.classes().collect::<Vec<_>>();
+        classes.sort_by(|a, b| b.1.fields.cmp(&a.1.fields).then(a.0.cmp(b.0)));
+
+        let mut table = Table::new();
+        table.add_row(row!["class", "created", "updated", "deleted", "fields"]);
+        for (name, stats) in classes {
+            table.add_row(row![
+                name,
+                stats.created,
+                stats.updated,
+                stats.deleted,
+                stats.fields
+            ]);
+        }
+        write!(f, "{}", table)
+    }
+}
+
 impl Display for Entities {
     fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
         let mut table = Table::new();
@@ -0,0 +1,63 @@
+use crate::{Entity, FieldValue};
+
+/// Property name resolved to field path once per class. Used by accessors
+/// generated with [`crate::CodeGenerator`].
+///
+/// Name may contain `{:04}` placeholders for array and vector indices, which
+/// are filled into the resolved path by [`PropertyAccessor::get`].
+///
+/// # Examples
+///
+/// ```no_run
+/// use source2_demo::prelude::*;
+///
+/// static PLAYER_NAME: PropertyAccessor =
+///     PropertyAccessor::new("m_vecPlayerData.{:04}.m_iszPlayerName");
+///
+/// fn player_name(player_resource: &Entity, player_id: usize) -> Option<String> {
+///     PLAYER_NAME.get(player_resource, &[player_id])?.try_into().ok()
+/// }
+/// ```
+#[derive(Debug)]
+pub struct PropertyAccessor {
+    name: &'static str,
+}
+
+impl PropertyAccessor {
+    pub const fn new(name: &'static str) -> Self {
+        PropertyAccessor { name }
+    }
+
+    /// Returns value of the property with given indices, `None` if the class
+    /// has no such property or it isn't set.
+    pub fn get<'e>(&'static self, entity: &'e Entity, indices: &[usize]) -> Option<&'e FieldValue> {
+        let key = self as *const PropertyAccessor as usize;
+        let class = entity.class();
+        let cached = class.field_paths.read().unwrap().get(&key).copied();
+
+        let mut path = cached.unwrap_or_else(|| {
+            let path = class
+                .serializer
+                .get_field_path_for_name(&self.name.replace("{:04}", "0000"))
+                .ok();
+            class.field_paths.write().unwrap().insert(key, path);
+            path
+        })?;
+
+        // Every name segment is one path component
+        let slots = self
+            .name
+            .split('.')
+            .enumerate()
+            .filter(|(_, segment)| *segment == "{:04}");
+        let mut indices = indices.iter();
+        for ((slot, _), &index) in slots.zip(indices.by_ref()) {
+            path.path[slot] = u16::try_from(index).ok()?;
+        }
+        if indices.next().is_some() {
+            return None;
+        }
+
+        entity.state.get_value(&path)
+    }
+}
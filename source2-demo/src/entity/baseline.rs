@@ -1,15 +1,16 @@
 use crate::entity::field::FieldState;
 use hashbrown::HashMap;
-use std::rc::Rc;
+use std::sync::Arc;
 
-#[derive(Default)]
+#[derive(Clone, Default)]
 pub(crate) struct BaselineContainer {
-    pub(crate) baselines: HashMap<i32, Rc<Vec<u8>>>,
+    pub(crate) baselines: HashMap<i32, Arc<Vec<u8>>>,
     pub(crate) states: HashMap<i32, FieldState>,
 }
 
 impl BaselineContainer {
-    pub(crate) fn add_baseline(&mut self, id: i32, baseline: Rc<Vec<u8>>) {
+    pub(crate) fn add_baseline(&mut self, id: i32, baseline: Arc<Vec<u8>>) {
         self.baselines.insert(id, baseline);
+        self.states.remove(&id);
     }
 }
@@ -0,0 +1,84 @@
+use crate::entity::field::FieldPath;
+use crate::{Entity, FieldValue};
+
+/// Property changes recorded by parser, requested by observers with
+/// [`crate::Observer::property_tracking`].
+#[derive(Debug, Clone, Copy, Default, Eq, PartialEq, Ord, PartialOrd)]
+pub enum PropertyTracking {
+    /// Changes aren't recorded.
+    #[default]
+    Disabled,
+    /// Changed properties and new values, [`PropertyChange::previous`] is
+    /// always `None`.
+    Paths,
+    /// Changed properties with previous and new values.
+    Values,
+}
+
+/// Entity property written by entity update. Passed to
+/// [`crate::Observer::on_property_change`].
+///
+/// # Examples
+///
+/// ```no_run
+/// use source2_demo::prelude::*;
+///
+/// #[derive(Default)]
+/// struct Deaths;
+///
+/// impl Observer for Deaths {
+///     fn on_property_change(
+///         &mut self,
+///         ctx: &Context,
+///         entity: &Entity,
+///         change: &PropertyChange,
+///     ) -> ObserverResult {
+///         if entity.class().matches("CDOTA_Unit_Hero_*") && change.matches(entity, "m_lifeState") {
+///             let state: i32 = change.value().try_into()?;
+///             if state == 1 {
+///                 println!("{} died at {}", entity.class().name(), ctx.tick());
+///             }
+///         }
+///         Ok(())
+///     }
+///
+///     fn property_tracking(&self) -> PropertyTracking {
+///         PropertyTracking::Paths
+///     }
+/// }
+/// ```
+#[derive(Clone, Debug)]
+pub struct PropertyChange {
+    pub(crate) path: FieldPath,
+    pub(crate) previous: Option<FieldValue>,
+    pub(crate) value: FieldValue,
+}
+
+impl PropertyChange {
+    /// Returns `true` if this change is for property with given name of
+    /// given entity. [`crate::PropertyFilter`] resolves the name once per
+    /// class.
+    pub fn matches(&self, entity: &Entity, name: &str) -> bool {
+        entity
+            .class
+            .serializer
+            .get_field_path_for_name(name)
+            .is_ok_and(|fp| fp == self.path)
+    }
+
+    /// Returns name of changed property of given entity.
+    pub fn name(&self, entity: &Entity) -> String {
+        entity.class.serializer.get_name_for_field_path(&self.path)
+    }
+
+    /// Value before update, `None` if property wasn't set or previous
+    /// values aren't tracked ([`PropertyTracking::Paths`]).
+    pub fn previous(&self) -> Option<&FieldValue> {
+        self.previous.as_ref()
+    }
+
+    /// Value written by update.
+    pub fn value(&self) -> &FieldValue {
+        &self.value
+    }
+}
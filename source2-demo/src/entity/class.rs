@@ -1,19 +1,21 @@
-use crate::entity::field::Serializer;
+use crate::entity::field::{FieldPath, Serializer};
 use crate::error::ClassError;
 use hashbrown::HashMap;
-use std::rc::Rc;
+use std::sync::{Arc, RwLock};
 
 /// Container for classes.
-#[derive(Default)]
+#[derive(Clone, Default)]
 pub struct Classes {
-    pub(crate) classes_vec: Vec<Rc<Class>>,
-    pub(crate) classes_by_name: HashMap<Box<str>, Rc<Class>>,
+    pub(crate) classes_vec: Vec<Arc<Class>>,
+    pub(crate) classes_by_name: HashMap<Box<str>, Arc<Class>>,
     pub(crate) class_id_size: u32,
 }
 
 impl Classes {
-    pub(crate) fn get_by_id_rc(&self, id: usize) -> &Rc<Class> {
-        &self.classes_vec[id]
+    pub(crate) fn get_by_id_arc(&self, id: usize) -> Result<&Arc<Class>, ClassError> {
+        self.classes_vec
+            .get(id)
+            .ok_or(ClassError::ClassNotFoundById(id as i32))
     }
 
     /// Iterator over all classes.
@@ -39,19 +41,34 @@ impl Classes {
 }
 
 /// Entity class
-#[derive(Clone)]
 pub struct Class {
     pub(crate) id: i32,
     pub(crate) name: Box<str>,
-    pub(crate) serializer: Rc<Serializer>,
+    pub(crate) serializer: Arc<Serializer>,
+    pub(crate) filters: RwLock<HashMap<usize, bool>>,
+    pub(crate) field_paths: RwLock<HashMap<usize, Option<FieldPath>>>,
+}
+
+impl Clone for Class {
+    fn clone(&self) -> Self {
+        Class {
+            id: self.id,
+            name: self.name.clone(),
+            serializer: self.serializer.clone(),
+            filters: RwLock::new(self.filters.read().unwrap().clone()),
+            field_paths: RwLock::new(self.field_paths.read().unwrap().clone()),
+        }
+    }
 }
 
 impl Class {
-    pub(crate) fn new(id: i32, name: Box<str>, serializer: Rc<Serializer>) -> Self {
+    pub(crate) fn new(id: i32, name: Box<str>, serializer: Arc<Serializer>) -> Self {
         Class {
             id,
             name,
             serializer,
+            filters: RwLock::default(),
+            field_paths: RwLock::default(),
         }
     }
 
@@ -66,4 +83,43 @@ impl Class {
     pub fn id(&self) -> i32 {
         self.id
     }
+
+    /// Returns `true` if class name matches given glob pattern, where `*`
+    /// matches any sequence of characters and `?` matches one character.
+    ///
+    /// ```
+    /// # use source2_demo::Class;
+    /// # fn check(class: &Class) {
+    /// class.matches("CDOTA_Unit_Hero_*");
+    /// # }
+    /// ```
+    pub fn matches(&self, pattern: &str) -> bool {
+        glob_match(pattern.as_bytes(), self.name.as_bytes())
+    }
+}
+
+fn glob_match(pattern: &[u8], name: &[u8]) -> bool {
+    let (mut p, mut n) = (0, 0);
+    let mut backtrack = None;
+    while n < name.len() {
+        match pattern.get(p) {
+            Some(b'*') => {
+                backtrack = Some((p, n));
+                p += 1;
+            }
+            Some(&c) if c == b'?' || c == name[n] => {
+                p += 1;
+                n += 1;
+            }
+            _ => match backtrack {
+                Some((bp, bn)) => {
+                    backtrack = Some((bp, bn + 1));
+                    p = bp + 1;
+                    n = bn + 1;
+                }
+                None => return false,
+            },
+        }
+    }
+    pattern[p..].iter().all(|&c| c == b'*')
 }
@@ -1,20 +1,59 @@
 use crate::error::EntityError;
-use crate::Entity;
+use crate::{Entity, EntityHandle};
+use hashbrown::HashMap;
 
 /// Container for entities.
+#[derive(Clone)]
 pub struct Entities {
     pub(crate) entities_vec: Vec<Option<Entity>>,
+    /// Sorted entity indices by class id.
+    pub(crate) by_class: Vec<Vec<u32>>,
+    pub(crate) class_ids: HashMap<Box<str>, i32>,
 }
 
 impl Default for Entities {
     fn default() -> Self {
         Entities {
             entities_vec: vec![None; 8192],
+            by_class: Vec::new(),
+            class_ids: HashMap::default(),
         }
     }
 }
 
 impl Entities {
+    pub(crate) fn insert(&mut self, index: usize, entity: Entity) -> &mut Entity {
+        self.remove(index);
+
+        let class_id = entity.class.id;
+        if !self.class_ids.contains_key(entity.class.name()) {
+            self.class_ids.insert(entity.class.name().into(), class_id);
+        }
+        if self.by_class.len() <= class_id as usize {
+            self.by_class.resize_with(class_id as usize + 1, Vec::new);
+        }
+        let indices = &mut self.by_class[class_id as usize];
+        if let Err(position) = indices.binary_search(&(index as u32)) {
+            indices.insert(position, index as u32);
+        }
+
+        self.entities_vec[index].insert(entity)
+    }
+
+    pub(crate) fn remove(&mut self, index: usize) -> Option<Entity> {
+        let entity = self.entities_vec.get_mut(index)?.take()?;
+        if let Some(indices) = self.by_class.get_mut(entity.class.id as usize) {
+            if let Ok(position) = indices.binary_search(&(index as u32)) {
+                indices.remove(position);
+            }
+        }
+        Some(entity)
+    }
+
+    pub(crate) fn clear(&mut self) {
+        *self = Entities::default();
+    }
+
     /// Iterator over all entities.
     /// # Examples
     ///
@@ -52,23 +91,76 @@ impl Entities {
             .ok_or(EntityError::IndexNotFound(index))
     }
 
-    /// Returns [`Entity`] for given handle.
+    /// Returns [`Entity`] for given handle. Serial number isn't checked, so
+    /// stale handle returns entity which reused the slot, see
+    /// [`Entities::resolve`].
     pub fn get_by_handle(&self, handle: usize) -> Result<&Entity, EntityError> {
         self.get_by_index(handle & 0x3fff)
             .map_err(|_| EntityError::HandleNotFound(handle))
     }
 
-    /// Returns [`Entity`] for given class id.
+    /// Returns [`Entity`] for given handle if it's not null and serial number
+    /// of entity in its slot matches.
+    pub fn resolve(&self, handle: impl Into<EntityHandle>) -> Option<&Entity> {
+        let handle = handle.into();
+        if handle.is_null() {
+            return None;
+        }
+        self.entities_vec
+            .get(handle.index())?
+            .as_ref()
+            .filter(|entity| entity.serial() == handle.serial())
+    }
+
+    /// Returns [`Entity`] with the lowest index for given class id.
     pub fn get_by_class_id(&self, id: i32) -> Result<&Entity, EntityError> {
-        self.iter()
-            .find(|&entity| entity.class().id() == id)
+        self.iter_by_class_id(id)
+            .next()
             .ok_or(EntityError::ClassIdNotFound(id))
     }
 
-    /// Returns [`Entity`] for given class name.
+    /// Returns [`Entity`] with the lowest index for given class name.
     pub fn get_by_class_name(&self, name: &str) -> Result<&Entity, EntityError> {
-        self.iter()
-            .find(|&entity| entity.class().name() == name)
+        self.iter_by_class_name(name)
+            .next()
             .ok_or(EntityError::ClassNameNotFound(name.to_string()))
     }
+
+    /// Iterator over all entities of given class id ordered by index.
+    pub fn iter_by_class_id(&self, id: i32) -> impl Iterator<Item = &Entity> {
+        self.by_class
+            .get(id as usize)
+            .map_or(&[][..], |indices| indices.as_slice())
+            .iter()
+            .filter_map(|&index| self.entities_vec[index as usize].as_ref())
+    }
+
+    /// Iterator over all entities of given class name ordered by index.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use source2_demo::prelude::*;
+    ///
+    /// #[derive(Default)]
+    /// struct Wards;
+    ///
+    /// impl Observer for Wards {
+    ///     fn on_tick_end(&mut self, ctx: &Context) -> ObserverResult {
+    ///         let wards = ctx
+    ///             .entities()
+    ///             .iter_by_class_name("CDOTA_NPC_Observer_Ward")
+    ///             .count();
+    ///         Ok(())
+    ///     }
+    /// }
+    /// ```
+    pub fn iter_by_class_name(&self, name: &str) -> impl Iterator<Item = &Entity> {
+        self.iter_by_class_id(self.class_ids.get(name).copied().unwrap_or(-1))
+    }
+
+    /// Returns all entities of given class name ordered by index.
+    pub fn get_all_by_class_name(&self, name: &str) -> Vec<&Entity> {
+        self.iter_by_class_name(name).collect()
+    }
 }
@@ -1,4 +1,5 @@
 use crate::entity::field::{FieldEncoder, FieldProperties, FieldType, FieldValue};
+use crate::parser::Game;
 use crate::reader::*;
 
 pub(crate) enum FieldDecoder {
@@ -20,7 +21,12 @@ pub(crate) enum FieldDecoder {
 
 impl FieldDecoder {
     #[inline]
-    pub(crate) fn from_field(field_type: &FieldType, properties: FieldProperties) -> Self {
+    #[cfg_attr(not(feature = "dota"), allow(unused_variables))]
+    pub(crate) fn from_field(
+        field_type: &FieldType,
+        properties: FieldProperties,
+        game: Game,
+    ) -> Self {
         match field_type.base.as_ref() {
             "bool" | "CBodyComponent" | "CPhysicsComponent" | "CRenderComponent" => {
                 FieldDecoder::Boolean
@@ -42,7 +48,7 @@ impl FieldDecoder {
             "int32" => FieldDecoder::Signed32,
 
             #[cfg(feature = "dota")]
-            "HeroID_t" => FieldDecoder::Signed32,
+            "HeroID_t" if game != Game::Deadlock => FieldDecoder::Signed32,
 
             "uint8" | "BloodType" => FieldDecoder::Unsigned8,
             "uint16" => FieldDecoder::Unsigned16,
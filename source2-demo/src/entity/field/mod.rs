@@ -13,16 +13,16 @@ pub(crate) use encoder::*;
 pub(crate) use model::*;
 pub(crate) use path::*;
 pub(crate) use properties::*;
-pub(crate) use r#type::*;
+pub use r#type::*;
 pub(crate) use serializer::*;
 pub(crate) use state::*;
 pub use value::*;
 
-use std::rc::Rc;
+use std::sync::Arc;
 
 pub(crate) struct Field {
     pub(crate) var_name: Box<str>,
-    pub(crate) field_type: Rc<FieldType>,
+    pub(crate) field_type: Arc<FieldType>,
     pub(crate) model: FieldModel,
 
     pub(crate) decoder: FieldDecoder,
@@ -1,11 +1,11 @@
 use crate::entity::field::serializer::Serializer;
 use crate::entity::field::FieldDecoder;
-use std::rc::Rc;
+use std::sync::Arc;
 
 pub(crate) enum FieldModel {
     Value,
     Array,
     ArrayVector(FieldDecoder),
-    Vector(Rc<Serializer>),
-    Pointer(Rc<Serializer>),
+    Vector(Arc<Serializer>),
+    Pointer(Arc<Serializer>),
 }
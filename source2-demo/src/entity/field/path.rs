@@ -14,16 +14,30 @@ impl Default for FieldPath {
     }
 }
 
+impl PartialEq for FieldPath {
+    #[inline]
+    fn eq(&self, other: &Self) -> bool {
+        self.last == other.last && self.path[..=self.last] == other.path[..=other.last]
+    }
+}
+
+impl Eq for FieldPath {}
+
 impl FieldPath {
     #[inline]
     pub(crate) fn push(&mut self, val: u16) {
+        if self.last + 1 == self.path.len() {
+            // Corrupted data, mark path as invalid instead of overflowing.
+            self.path[self.last] = u16::MAX;
+            return;
+        }
         self.last += 1;
         self.path[self.last] = val;
     }
 
     #[inline]
     pub(crate) fn pop(&mut self, n: usize) {
-        for _ in 0..n {
+        for _ in 0..n.min(self.last) {
             self.path[self.last] = 0;
             self.last -= 1;
         }
@@ -31,12 +45,16 @@ impl FieldPath {
 
     #[inline]
     pub(crate) fn inc(&mut self, n: usize, val: u16) {
-        self.path[n] = self.path[n].wrapping_add(val)
+        if let Some(x) = self.path.get_mut(n) {
+            *x = x.wrapping_add(val)
+        }
     }
 
     #[inline]
     pub(crate) fn sub(&mut self, n: usize, val: u16) {
-        self.path[n] = self.path[n].wrapping_sub(val)
+        if let Some(x) = self.path.get_mut(n) {
+            *x = x.wrapping_sub(val)
+        }
     }
 
     #[inline]
@@ -1,13 +1,12 @@
 use crate::entity::field::*;
 use crate::error::SerializerError;
 use hashbrown::HashMap;
-use std::cell::RefCell;
-use std::rc::Rc;
+use std::sync::{Arc, RwLock};
 
-#[derive(Clone, Default)]
+#[derive(Default)]
 pub(crate) struct Serializer {
-    pub(crate) fields: Vec<Rc<Field>>,
-    pub(crate) fp_cache: RefCell<HashMap<Box<str>, FieldPath>>,
+    pub(crate) fields: Vec<Arc<Field>>,
+    pub(crate) fp_cache: RwLock<HashMap<Box<str>, FieldPath>>,
 }
 
 impl Serializer {
@@ -65,7 +64,8 @@ impl Serializer {
                 }
                 FieldModel::ArrayVector(_) => {
                     if i == fp.last {
-                        return current_field.field_type.as_ref().generic.as_ref().unwrap();
+                        let field_type = current_field.field_type.as_ref();
+                        return field_type.generic.as_deref().unwrap_or(field_type);
                     }
                     return current_field.field_type.as_ref();
                 }
@@ -91,23 +91,23 @@ impl Serializer {
     }
 
     #[inline]
-    pub(crate) fn get_decoder_for_field_path(&self, fp: &FieldPath) -> &FieldDecoder {
+    pub(crate) fn get_decoder_for_field_path(&self, fp: &FieldPath) -> Option<&FieldDecoder> {
         let mut i = 0;
         let mut current_serializer = self;
-        let mut current_field = &current_serializer.fields[fp.path[i] as usize];
+        let mut current_field = current_serializer.fields.get(fp.path[i] as usize)?;
         loop {
             i += 1;
             match &current_field.model {
-                FieldModel::Value | FieldModel::Array => return &current_field.decoder,
+                FieldModel::Value | FieldModel::Array => return Some(&current_field.decoder),
                 FieldModel::ArrayVector(decoder) => {
                     if i == fp.last {
-                        return decoder;
+                        return Some(decoder);
                     }
-                    return &current_field.decoder;
+                    return Some(&current_field.decoder);
                 }
                 FieldModel::Vector(serializer) => {
                     if i >= fp.last {
-                        return &current_field.decoder;
+                        return Some(&current_field.decoder);
                     }
 
                     i += 1;
@@ -116,19 +116,19 @@ impl Serializer {
                 }
                 FieldModel::Pointer(serializer) => {
                     if i > fp.last {
-                        return &current_field.decoder;
+                        return Some(&current_field.decoder);
                     }
 
                     current_serializer = serializer;
                 }
             }
-            current_field = &current_serializer.fields[fp.path[i] as usize];
+            current_field = current_serializer.fields.get(fp.path[i] as usize)?;
         }
     }
 
     #[inline]
     pub(crate) fn get_field_path_for_name(&self, name: &str) -> Result<FieldPath, SerializerError> {
-        if !self.fp_cache.borrow().contains_key(name) {
+        if !self.fp_cache.read().unwrap().contains_key(name) {
             let mut current_serializer = self;
             let mut fp = FieldPath::default();
             let mut offset = 0;
@@ -138,9 +138,9 @@ impl Serializer {
                         fp.path[fp.last] = i as u16;
                         break 'outer;
                     }
-                    if name[offset..]
+                    if name
                         .as_bytes()
-                        .get(f.var_name.len())
+                        .get(offset + f.var_name.len())
                         .is_some_and(|&b| b == b'.')
                         && &name[offset..(offset + f.var_name.len())] == f.var_name.as_ref()
                     {
@@ -149,12 +149,16 @@ impl Serializer {
                         offset += f.var_name.len() + 1;
                         match &f.model {
                             FieldModel::Array | FieldModel::ArrayVector(_) => {
-                                fp.path[fp.last] = name[offset..].parse::<u16>().unwrap();
+                                fp.path[fp.last] = name[offset..]
+                                    .parse::<u16>()
+                                    .map_err(|_| SerializerError::NoFieldPath(name.to_string()))?;
                                 break 'outer;
                             }
                             FieldModel::Vector(serializer) => {
-                                fp.path[fp.last] =
-                                    name[offset..(offset + 4)].parse::<u16>().unwrap();
+                                fp.path[fp.last] = name
+                                    .get(offset..(offset + 4))
+                                    .and_then(|index| index.parse::<u16>().ok())
+                                    .ok_or_else(|| SerializerError::NoFieldPath(name.to_string()))?;
                                 fp.last += 1;
                                 offset += 5;
                                 current_serializer = serializer;
@@ -170,9 +174,9 @@ impl Serializer {
                 }
                 return Err(SerializerError::NoFieldPath(name.to_string()));
             }
-            self.fp_cache.borrow_mut().insert(name.into(), fp);
+            self.fp_cache.write().unwrap().insert(name.into(), fp);
         }
-        Ok(self.fp_cache.borrow()[name])
+        Ok(self.fp_cache.read().unwrap()[name])
     }
 
     pub(crate) fn get_field_paths<'a>(
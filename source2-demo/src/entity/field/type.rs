@@ -1,13 +1,35 @@
+/// Networked type of entity property, e.g. `CNetworkUtlVectorBase< CHandle< CBaseEntity > >`
+/// or `uint16[10]`.
 #[derive(Clone, Debug)]
-pub(crate) struct FieldType {
-    pub base: Box<str>,
-    pub generic: Option<Box<FieldType>>,
-    pub pointer: bool,
-    pub count: Option<i32>,
+pub struct FieldType {
+    pub(crate) base: Box<str>,
+    pub(crate) generic: Option<Box<FieldType>>,
+    pub(crate) pointer: bool,
+    pub(crate) count: Option<i32>,
 }
 
 impl FieldType {
-    pub fn new(name: &str) -> Self {
+    /// Returns type name without generic argument, pointer and array size.
+    pub fn base(&self) -> &str {
+        &self.base
+    }
+
+    /// Returns generic argument, e.g. `CHandle< CBaseEntity >` of
+    /// `CNetworkUtlVectorBase< CHandle< CBaseEntity > >`.
+    pub fn generic(&self) -> Option<&FieldType> {
+        self.generic.as_deref()
+    }
+
+    pub fn is_pointer(&self) -> bool {
+        self.pointer
+    }
+
+    /// Returns size of fixed array type.
+    pub fn count(&self) -> Option<i32> {
+        self.count
+    }
+
+    pub(crate) fn new(name: &str) -> Self {
         let mut base_end = name.len();
         let mut pointer = false;
         let mut count = None;
@@ -29,18 +51,18 @@ impl FieldType {
         }
 
         if let Some(open_bracket_pos) = name.find('[') {
-            let close_bracket_pos = name.find(']').unwrap();
+            let close_bracket_pos = name.find(']').unwrap_or(name.len()).max(open_bracket_pos + 1);
             count = Some(match &name[(open_bracket_pos + 1)..close_bracket_pos] {
                 "MAX_ITEM_STOCKS" => 8,
                 "MAX_ABILITY_DRAFT_ABILITIES" => 48,
                 "DOTA_ABILITY_DRAFT_HEROES_PER_GAME" => 10,
-                s => s.parse::<i32>().unwrap(),
+                s => s.parse::<i32>().unwrap_or_default(),
             });
             base_end = open_bracket_pos;
         }
 
         if let Some(open_angle_pos) = name.find('<') {
-            let close_angle_pos = name.rfind('>').unwrap();
+            let close_angle_pos = name.rfind('>').unwrap_or(name.len()).max(open_angle_pos + 1);
             generic = Some(Box::new(FieldType::new(
                 name[(open_angle_pos + 1)..close_angle_pos].trim(),
             )));
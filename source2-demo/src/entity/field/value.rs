@@ -1,4 +1,5 @@
 use crate::error::FieldValueError;
+use crate::EntityHandle;
 
 /// Special type for [`Entity`](crate::Entity) field value that can be converted
 /// into Rust type using `try_into`.
@@ -325,6 +326,29 @@ impl TryInto<bool> for &FieldValue {
     }
 }
 
+impl TryInto<EntityHandle> for FieldValue {
+    type Error = FieldValueError;
+
+    fn try_into(self) -> Result<EntityHandle, FieldValueError> {
+        (&self).try_into()
+    }
+}
+
+impl TryInto<EntityHandle> for &FieldValue {
+    type Error = FieldValueError;
+
+    fn try_into(self) -> Result<EntityHandle, FieldValueError> {
+        if let FieldValue::Unsigned32(x) = self {
+            Ok(EntityHandle::from(*x))
+        } else {
+            Err(FieldValueError::ConversionError(
+                format!("{:?}", self),
+                "EntityHandle".to_string(),
+            ))
+        }
+    }
+}
+
 macro_rules! impl_try_into_for_integers {
     ($target:ty) => {
         impl TryInto<$target> for FieldValue {
@@ -0,0 +1,167 @@
+use crate::{Class, Entity, PropertyChange};
+use regex::Regex;
+use std::sync::OnceLock;
+
+/// Pattern of [`ClassFilter`].
+#[derive(Debug, Clone, Copy)]
+pub enum ClassPattern {
+    /// Glob pattern, see [`Class::matches`].
+    Glob(&'static str),
+    /// Regular expression, matches if found anywhere in class name.
+    Regex(&'static str),
+}
+
+/// Set of class name patterns. Used by `#[on_entity]` and
+/// `#[on_property_change]` attributes with glob, regex or several patterns.
+///
+/// Match result is cached in [`Class`], so patterns are checked once per
+/// class instead of every callback.
+///
+/// # Examples
+///
+/// ```no_run
+/// use source2_demo::prelude::*;
+///
+/// #[derive(Default)]
+/// struct Units;
+///
+/// #[observer]
+/// impl Units {
+///     #[on_entity("CDOTA_Unit_Hero_*", regex = "^CDOTA_BaseNPC_(Creep|Tower)")]
+///     fn unit(&mut self, entity: &Entity) -> ObserverResult {
+///         Ok(())
+///     }
+/// }
+/// ```
+///
+/// ```no_run
+/// use source2_demo::prelude::*;
+///
+/// static UNITS: ClassFilter = ClassFilter::new(&[
+///     ClassPattern::Glob("CDOTA_Unit_Hero_*"),
+///     ClassPattern::Regex("^CDOTA_BaseNPC_(Creep|Tower)"),
+/// ]);
+///
+/// #[derive(Default)]
+/// struct Units;
+///
+/// impl Observer for Units {
+///     fn on_entity(
+///         &mut self,
+///         ctx: &Context,
+///         event: EntityEvents,
+///         entity: &Entity,
+///     ) -> ObserverResult {
+///         if UNITS.matches(entity.class()) {
+///             // ...
+///         }
+///         Ok(())
+///     }
+/// }
+/// ```
+#[derive(Debug)]
+pub struct ClassFilter {
+    patterns: &'static [ClassPattern],
+    regexes: OnceLock<Vec<Regex>>,
+}
+
+impl ClassFilter {
+    pub const fn new(patterns: &'static [ClassPattern]) -> Self {
+        ClassFilter {
+            patterns,
+            regexes: OnceLock::new(),
+        }
+    }
+
+    /// Returns `true` if class name matches any of patterns.
+    ///
+    /// # Panics
+    ///
+    /// Panics if regex pattern is invalid.
+    pub fn matches(&'static self, class: &Class) -> bool {
+        let key = self as *const ClassFilter as usize;
+        if let Some(&matches) = class.filters.read().unwrap().get(&key) {
+            return matches;
+        }
+
+        let matches = self.matches_name(class);
+        class.filters.write().unwrap().insert(key, matches);
+        matches
+    }
+
+    fn matches_name(&self, class: &Class) -> bool {
+        let regexes = self.regexes.get_or_init(|| {
+            self.patterns
+                .iter()
+                .filter_map(|pattern| match pattern {
+                    ClassPattern::Regex(regex) => {
+                        Some(Regex::new(regex).expect("Invalid class regex"))
+                    }
+                    ClassPattern::Glob(_) => None,
+                })
+                .collect()
+        });
+
+        self.patterns.iter().any(|pattern| match pattern {
+            ClassPattern::Glob(glob) => class.matches(glob),
+            ClassPattern::Regex(_) => false,
+        }) || regexes.iter().any(|regex| regex.is_match(class.name()))
+    }
+}
+
+/// Property name resolved to field path once per class. Used by
+/// `#[on_property_change]` attribute, so changes are compared by path
+/// instead of looking the name up for every change.
+///
+/// # Examples
+///
+/// ```no_run
+/// use source2_demo::prelude::*;
+///
+/// static LIFE_STATE: PropertyFilter = PropertyFilter::new("m_lifeState");
+///
+/// #[derive(Default)]
+/// struct Deaths;
+///
+/// impl Observer for Deaths {
+///     fn on_property_change(
+///         &mut self,
+///         ctx: &Context,
+///         entity: &Entity,
+///         change: &PropertyChange,
+///     ) -> ObserverResult {
+///         if LIFE_STATE.matches(entity, change) {
+///             // ...
+///         }
+///         Ok(())
+///     }
+///
+///     fn property_tracking(&self) -> PropertyTracking {
+///         PropertyTracking::Paths
+///     }
+/// }
+/// ```
+#[derive(Debug)]
+pub struct PropertyFilter {
+    name: &'static str,
+}
+
+impl PropertyFilter {
+    pub const fn new(name: &'static str) -> Self {
+        PropertyFilter { name }
+    }
+
+    /// Returns `true` if change is for this property of given entity.
+    pub fn matches(&'static self, entity: &Entity, change: &PropertyChange) -> bool {
+        let key = self as *const PropertyFilter as usize;
+        let class = entity.class();
+        let cached = class.field_paths.read().unwrap().get(&key).copied();
+
+        let path = cached.unwrap_or_else(|| {
+            let path = class.serializer.get_field_path_for_name(self.name).ok();
+            class.field_paths.write().unwrap().insert(key, path);
+            path
+        });
+        path.is_some_and(|path| path == change.path)
+    }
+}
@@ -0,0 +1,64 @@
+/// Entity handle stored in properties such as `m_hOwnerEntity` or
+/// `m_hAssignedHero`: entity index in lower 14 bits and serial number in
+/// upper bits. Resolved with [`crate::Entities::resolve`].
+///
+/// # Examples
+///
+/// ```
+/// use source2_demo::prelude::*;
+///
+/// #[derive(Default)]
+/// struct Owners;
+///
+/// impl Observer for Owners {
+///     fn on_entity(
+///         &mut self,
+///         ctx: &Context,
+///         event: EntityEvents,
+///         entity: &Entity,
+///     ) -> ObserverResult {
+///         let handle: EntityHandle = property!(entity, "m_hOwnerEntity");
+///         if let Some(owner) = ctx.entities().resolve(handle) {
+///             println!("{} owned by {}", entity.class().name(), owner.class().name());
+///         }
+///         Ok(())
+///     }
+/// }
+/// ```
+#[derive(Debug, Clone, Copy, Eq, PartialEq, Hash)]
+pub struct EntityHandle(u32);
+
+impl EntityHandle {
+    /// Handle that doesn't point to any entity.
+    pub const NULL: EntityHandle = EntityHandle(u32::MAX);
+
+    /// Returns `true` for null handle, `0xFFFFFF` or `u32::MAX`.
+    pub fn is_null(&self) -> bool {
+        self.0 == 0xFFFFFF || self.0 == u32::MAX
+    }
+
+    pub fn index(&self) -> usize {
+        (self.0 & 0x3fff) as usize
+    }
+
+    pub fn serial(&self) -> u32 {
+        self.0 >> 14
+    }
+
+    /// Returns raw handle value.
+    pub fn raw(&self) -> u32 {
+        self.0
+    }
+}
+
+impl From<u32> for EntityHandle {
+    fn from(handle: u32) -> Self {
+        EntityHandle(handle)
+    }
+}
+
+impl From<EntityHandle> for u32 {
+    fn from(handle: EntityHandle) -> Self {
+        handle.0
+    }
+}
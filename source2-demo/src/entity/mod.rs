@@ -1,16 +1,24 @@
+mod accessor;
 mod baseline;
+mod change;
 mod class;
 mod container;
+mod filter;
+mod handle;
 
+pub use accessor::*;
 pub(crate) use baseline::*;
 pub(crate) mod field;
+pub use change::*;
 pub use class::*;
 pub use container::*;
+pub use filter::*;
+pub use handle::*;
 
 use crate::error::EntityError;
 use crate::field::{FieldPath, FieldState};
-use crate::FieldValue;
-use std::rc::Rc;
+use crate::{FieldType, FieldValue};
+use std::sync::Arc;
 
 #[derive(Debug, Clone, Copy, Eq, PartialEq)]
 pub enum EntityEvents {
@@ -21,12 +29,12 @@ pub enum EntityEvents {
 
 impl EntityEvents {
     #[inline]
-    pub(crate) fn from_cmd(cmd: u32) -> Self {
+    pub(crate) fn from_cmd(cmd: u32) -> Result<Self, EntityError> {
         match cmd {
-            0 => EntityEvents::Updated,
-            2 => EntityEvents::Created,
-            3 => EntityEvents::Deleted,
-            _ => unreachable!(),
+            0 => Ok(EntityEvents::Updated),
+            2 => Ok(EntityEvents::Created),
+            3 => Ok(EntityEvents::Deleted),
+            _ => Err(EntityError::UnknownCommand(cmd)),
         }
     }
 }
@@ -35,12 +43,12 @@ impl EntityEvents {
 pub struct Entity {
     index: u32,
     serial: u32,
-    pub(crate) class: Rc<Class>,
+    pub(crate) class: Arc<Class>,
     pub(crate) state: FieldState,
 }
 
 impl Entity {
-    pub(crate) fn new(index: u32, serial: u32, class: Rc<Class>, state: FieldState) -> Self {
+    pub(crate) fn new(index: u32, serial: u32, class: Arc<Class>, state: FieldState) -> Self {
         Entity {
             index,
             serial,
@@ -102,6 +110,34 @@ impl Entity {
         self.get_property_by_field_path(&self.class.serializer.get_field_path_for_name(name)?)
     }
 
+    /// Iterator over all set properties with their names, values and types.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use source2_demo::prelude::*;
+    ///
+    /// fn dump(entity: &Entity) {
+    ///     for (name, value, field_type) in entity.properties() {
+    ///         println!("{name} ({field_type}): {value}");
+    ///     }
+    /// }
+    /// ```
+    pub fn properties(&self) -> impl Iterator<Item = (String, &FieldValue, &FieldType)> {
+        let serializer = &self.class.serializer;
+        serializer
+            .get_field_paths(&mut FieldPath::default(), &self.state)
+            .into_iter()
+            .filter_map(move |fp| {
+                let value = self.state.get_value(&fp)?;
+                Some((
+                    serializer.get_name_for_field_path(&fp),
+                    value,
+                    serializer.get_type_for_field_path(&fp),
+                ))
+            })
+    }
+
     pub(crate) fn get_property_by_field_path(
         &self,
         fp: &FieldPath,
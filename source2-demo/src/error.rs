@@ -25,12 +25,49 @@ pub enum ParserError {
     #[error(transparent)]
     ObserverError(#[from] anyhow::Error),
 
+    #[error(transparent)]
+    Io(#[from] std::io::Error),
+
+    #[error(transparent)]
+    KeyframeIndex(#[from] KeyframeIndexError),
+
+    #[error(transparent)]
+    Checkpoint(#[from] CheckpointError),
+
+    #[error(transparent)]
+    Regex(#[from] regex::Error),
+
     #[error("Wrong CDemoFileInfo offset")]
     ReplayEncodingError,
 
+    #[error("Unknown demo command {0}")]
+    UnknownDemoCommand(i32),
+
+    #[error("Full packet without {0}")]
+    IncompleteFullPacket(&'static str),
+
+    #[error("Serializer not found for the given name {0}")]
+    SerializerNotFound(String),
+
+    #[error("Corrupted replay at offset {offset} (tick {tick}): {source}")]
+    Corrupted {
+        offset: usize,
+        tick: u32,
+        source: Box<ParserError>,
+    },
+
     #[error("Supports only Source 2 replays")]
     WrongMagic,
 
+    #[error("Seeking is not supported for streamed replays")]
+    SeekNotSupported,
+
+    #[error("Parsing was cancelled")]
+    Cancelled,
+
+    #[error("Invalid tick range {start}..={end}")]
+    InvalidTickRange { start: u32, end: u32 },
+
     #[cfg(feature = "dota")]
     #[error(transparent)]
     CombatLog(#[from] CombatLogError),
@@ -68,6 +105,15 @@ pub enum EntityError {
 
     #[error(transparent)]
     FieldPathNotFound(#[from] SerializerError),
+
+    #[error("Unknown entity command {0}")]
+    UnknownCommand(u32),
+
+    #[error("No baseline found for class with id {0}")]
+    BaselineNotFound(i32),
+
+    #[error("Corrupted entity data at index {0}: {1}")]
+    CorruptedData(usize, #[source] SerializerError),
 }
 
 #[derive(thiserror::Error, Debug)]
@@ -80,6 +126,10 @@ pub enum FieldValueError {
 pub enum GameEventError {
     #[error("Unknown key: {0}")]
     UnknownKey(String),
+    #[error("Unknown event id: {0}")]
+    UnknownEvent(i32),
+    #[error("Unknown event key type: {0}")]
+    UnknownKeyType(i32),
     #[error("Conversion error: {0} -> {1}")]
     ConversionError(String, String),
 }
@@ -88,6 +138,9 @@ pub enum GameEventError {
 pub enum SerializerError {
     #[error("No field path for given name {0}")]
     NoFieldPath(String),
+
+    #[error("Invalid field path {0}")]
+    InvalidFieldPath(String),
 }
 
 #[derive(thiserror::Error, Debug)]
@@ -100,6 +153,57 @@ pub enum StringTableError {
 
     #[error("String table entry not found for the given index {0} ({1})")]
     RowNotFoundByIndex(i32, String),
+
+    #[error("Corrupted string table data ({0})")]
+    CorruptedData(String),
+
+    #[error("Invalid instance baseline key {0}")]
+    InvalidBaselineKey(String),
+}
+
+#[derive(thiserror::Error, Debug)]
+pub enum KeyframeIndexError {
+    #[error("Not a keyframe index file")]
+    WrongMagic,
+
+    #[error("Unsupported keyframe index version {0}")]
+    UnsupportedVersion(u32),
+
+    #[error("Keyframe index file is truncated")]
+    Truncated,
+
+    #[error("Keyframe index doesn't match the replay")]
+    Stale,
+
+    #[error("Keyframe index of truncated replay is incomplete")]
+    Incomplete,
+}
+
+#[derive(thiserror::Error, Debug)]
+pub enum CheckpointError {
+    #[error("Not a parser checkpoint")]
+    WrongMagic,
+
+    #[error("Unsupported checkpoint version {0}")]
+    UnsupportedVersion(u32),
+
+    #[error("Checkpoint is truncated")]
+    Truncated,
+
+    #[error("Checkpoint is corrupted")]
+    Corrupted,
+
+    #[error("Checkpoint was taken from another replay")]
+    ReplayMismatch,
+
+    #[error("Checkpoint was written with features {snapshot:#x}, this build has {build:#x}")]
+    FeatureMismatch { snapshot: u32, build: u32 },
+
+    #[error("Checkpoint has {0} unread bytes at the end")]
+    TrailingData(usize),
+
+    #[error("Class with id {0} not found")]
+    ClassNotFound(i32),
 }
 
 #[derive(thiserror::Error, Debug)]
@@ -1,10 +1,10 @@
 use hashbrown::HashMap;
-use std::rc::Rc;
+use std::sync::Arc;
 
 pub(crate) struct GameEventDefinition {
     pub(crate) name: String,
-    pub(crate) keys: Vec<Rc<GameEventKey>>,
-    pub(crate) name_to_key: HashMap<String, Rc<GameEventKey>>,
+    pub(crate) keys: Vec<Arc<GameEventKey>>,
+    pub(crate) name_to_key: HashMap<String, Arc<GameEventKey>>,
 }
 
 #[derive(Debug)]
@@ -1,11 +1,11 @@
 use crate::event::*;
 use hashbrown::HashMap;
 use source2_demo_protobufs::CSvcMsgGameEventList;
-use std::rc::Rc;
+use std::sync::Arc;
 
-#[derive(Default)]
+#[derive(Clone, Default)]
 pub struct GameEventList {
-    pub(crate) list: HashMap<i32, Rc<GameEventDefinition>>,
+    pub(crate) list: HashMap<i32, Arc<GameEventDefinition>>,
 }
 
 impl GameEventList {
@@ -20,13 +20,13 @@ impl GameEventList {
                     .into_iter()
                     .enumerate()
                     .map(|(i, key)| {
-                        Rc::new(GameEventKey {
+                        Arc::new(GameEventKey {
                             id: i as i32,
                             name: key.name().into(),
                         })
                     })
                     .collect::<Vec<_>>();
-                let definition = Rc::new(GameEventDefinition {
+                let definition = Arc::new(GameEventDefinition {
                     name: descriptor.name().into(),
                     keys: keys.clone(),
                     name_to_key: keys
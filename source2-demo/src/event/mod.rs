@@ -20,26 +20,32 @@ pub struct GameEvent<'a> {
 }
 
 impl<'a> GameEvent<'a> {
-    pub fn new(list: &'a GameEventList, ge: CSvcMsgGameEvent) -> Self {
+    pub fn new(list: &'a GameEventList, ge: CSvcMsgGameEvent) -> Result<Self, GameEventError> {
         let id = ge.eventid();
+        if !list.list.contains_key(&id) {
+            return Err(GameEventError::UnknownEvent(id));
+        }
+
         let keys = ge
             .keys
             .iter()
-            .map(|key| match key.r#type() {
-                1 => EventValue::String(key.val_string().into()),
-                2 => EventValue::Float(key.val_float()),
-                3 => EventValue::Int(key.val_long()),
-                4 => EventValue::Int(key.val_short()),
-                5 => EventValue::Byte(key.val_byte() as u8),
-                6 => EventValue::Bool(key.val_bool()),
-                7 => EventValue::U64(key.val_uint64()),
-                8 => EventValue::Int(key.val_long()),
-                9 => EventValue::Int(key.val_short()),
-                _ => unreachable!("Unknown event type: {}", key.r#type()),
+            .map(|key| {
+                Ok(match key.r#type() {
+                    1 => EventValue::String(key.val_string().into()),
+                    2 => EventValue::Float(key.val_float()),
+                    3 => EventValue::Int(key.val_long()),
+                    4 => EventValue::Int(key.val_short()),
+                    5 => EventValue::Byte(key.val_byte() as u8),
+                    6 => EventValue::Bool(key.val_bool()),
+                    7 => EventValue::U64(key.val_uint64()),
+                    8 => EventValue::Int(key.val_long()),
+                    9 => EventValue::Int(key.val_short()),
+                    x => return Err(GameEventError::UnknownKeyType(x)),
+                })
             })
-            .collect::<Vec<_>>();
+            .collect::<Result<Vec<_>, _>>()?;
 
-        Self { id, list, keys }
+        Ok(Self { id, list, keys })
     }
 
     pub fn id(&self) -> i32 {
@@ -62,6 +68,8 @@ impl<'a> GameEvent<'a> {
             .name_to_key
             .get(key)
             .ok_or_else(|| GameEventError::UnknownKey(key.to_string()))?;
-        Ok(&self.keys[key.id as usize])
+        self.keys
+            .get(key.id as usize)
+            .ok_or_else(|| GameEventError::UnknownKey(key.name.clone()))
     }
 }
@@ -15,7 +15,11 @@ pub mod proto {
 }
 
 pub mod prelude {
-    pub use crate::entity::{Entity, EntityEvents};
+    pub use crate::entity::field::FieldValue;
+    pub use crate::entity::{
+        ClassFilter, ClassPattern, Entity, EntityEvents, EntityHandle, PropertyAccessor,
+        PropertyChange, PropertyFilter, PropertyTracking,
+    };
     pub use crate::event::{EventValue, GameEvent, GameEventList};
     pub use crate::parser::*;
     pub use crate::string_table::*;
@@ -42,7 +46,7 @@ pub mod prelude {
     pub use crate::proto::ECitadelGameEvents;
 }
 
-pub use crate::entity::field::FieldValue;
+pub use crate::entity::field::{FieldType, FieldValue};
 pub use crate::entity::*;
 pub use crate::event::*;
 pub use crate::parser::*;
@@ -1,15 +1,9 @@
 #[macro_export]
 macro_rules! try_observers {
     ($self:ident, $method:ident ( $($arg:expr),* )) => {
-        {
-            use std::rc::Rc;
-            use std::cell::RefCell;
-            use crate::Observer;
-
-            $self.observers
-                .iter()
-                .try_for_each(|obs: &Rc<RefCell<dyn Observer>>| obs.borrow_mut().$method($($arg),*))
-        }
+        $self.observers
+            .iter()
+            .try_for_each(|entry| $crate::parser::lock(&entry.observer).$method($($arg),*))
     };
 }
 
@@ -0,0 +1,624 @@
+use crate::entity::field::{FieldDecoder, FieldEncoder, FieldModel, FieldValue, Serializer};
+use crate::entity::{Class, EntityEvents};
+use crate::error::{EntityError, ParserError};
+use crate::parser::demo::{DemoCommands, DemoMessages};
+use crate::parser::writer::{write_message, BitWriter};
+#[cfg(feature = "dota")]
+use crate::parser::Game;
+use crate::parser::Parser;
+use crate::proto::*;
+use crate::reader::*;
+use crate::StringTable;
+use hashbrown::HashMap;
+use regex::Regex;
+use std::ops::Range;
+use std::sync::Arc;
+
+const NAME_FIELDS: [&str; 1] = ["m_iszPlayerName"];
+const STEAM_ID_FIELDS: [&str; 2] = ["m_iPlayerSteamID", "m_steamID"];
+
+/// Steam ID of the first individual account, aliases are counted from it.
+const FIRST_STEAM_ID: u64 = 76561197960265728;
+
+/// Shorter names aren't replaced in chat, they match ordinary words.
+const MIN_CHAT_NAME_LEN: usize = 3;
+
+/// Rewrites replay with player names replaced by `Player N` and Steam IDs by
+/// consecutive fake ones. Same player gets the same alias everywhere:
+///
+/// - `userinfo` string table rows
+/// - `m_iszPlayerName`, `m_iPlayerSteamID` and `m_steamID` entity fields
+///   (e.g. `CDOTA_PlayerResource` player data)
+/// - player names mentioned as whole words in [`CDotaUserMsgChatMessage`],
+///   except names shorter than 3 characters
+/// - players in [`CDemoFileInfo`] game info
+///
+/// Only bits of these values are rewritten, everything else is copied as is,
+/// so anonymized replay parses identically apart from those fields.
+///
+/// # Examples
+///
+/// ```no_run
+/// use source2_demo::prelude::*;
+///
+/// # fn main() -> Result<(), Box<dyn std::error::Error>> {
+/// let replay = std::fs::read("replay.dem")?;
+/// let anonymized = Anonymizer::new(&replay)?.run()?;
+/// std::fs::write("anonymized.dem", anonymized)?;
+/// # Ok(())
+/// # }
+/// ```
+pub struct Anonymizer<'a> {
+    replay: &'a [u8],
+    parser: Parser<'a>,
+    aliases: Aliases,
+    player_classes: HashMap<i32, bool>,
+}
+
+impl<'a> Anonymizer<'a> {
+    pub fn new(replay: &'a [u8]) -> Result<Self, ParserError> {
+        let parser = Parser::new(replay)?;
+
+        let mut aliases = Aliases::default();
+        let mut replay_info = parser.replay_info.clone();
+        aliases.anonymize_file_info(&mut replay_info);
+
+        Ok(Anonymizer {
+            replay,
+            parser,
+            aliases,
+            player_classes: HashMap::default(),
+        })
+    }
+
+    /// Returns anonymized replay.
+    pub fn run(mut self) -> Result<Vec<u8>, ParserError> {
+        let mut out = Vec::with_capacity(self.replay.len());
+        // Header is kept as is, only file info offset is patched at the end
+        out.extend_from_slice(&self.replay[..16]);
+
+        let mut file_info_offset = 0;
+
+        loop {
+            let offset = self.parser.reader.position();
+            let Some(message) = self.parser.read_next_message()? else {
+                break;
+            };
+            let end = self.parser.reader.position();
+
+            self.parser.on_tick_start(message.tick)?;
+
+            if message.msg_type == EDemoCommands::DemFileInfo {
+                file_info_offset = out.len();
+            }
+
+            match self.rewrite(message.msg_type, &message.buf)? {
+                Some(buf) => {
+                    let compressed = Reader::new(&self.replay[offset..end]).read_var_u32()
+                        & EDemoCommands::DemIsCompressed as u32
+                        != 0;
+                    write_message(&mut out, message.msg_type, message.tick, &buf, compressed)?
+                }
+                None => out.extend_from_slice(&self.replay[offset..end]),
+            }
+        }
+
+        out[8..12].copy_from_slice(&(file_info_offset as u32).to_le_bytes());
+
+        Ok(out)
+    }
+
+    /// Applies message to parser state and returns rewritten message, `None`
+    /// if it has nothing to anonymize.
+    fn rewrite(
+        &mut self,
+        msg_type: EDemoCommands,
+        msg: &[u8],
+    ) -> Result<Option<Vec<u8>>, ParserError> {
+        let rewritten = match msg_type {
+            EDemoCommands::DemPacket | EDemoCommands::DemSignonPacket => {
+                let mut packet = CDemoPacket::decode(msg)?;
+                return Ok(self.rewrite_packet(packet.data(), true)?.map(|data| {
+                    packet.data = Some(data);
+                    packet.encode_to_vec()
+                }));
+            }
+            EDemoCommands::DemFullPacket => {
+                let mut full_packet = CDemoFullPacket::decode(msg)?;
+                let mut changed = false;
+                if let Some(string_tables) = full_packet.string_table.as_mut() {
+                    changed |= self.aliases.anonymize_string_tables(string_tables);
+                }
+                if let Some(packet) = full_packet.packet.as_mut() {
+                    if let Some(data) = self.rewrite_packet(packet.data(), false)? {
+                        packet.data = Some(data);
+                        changed = true;
+                    }
+                }
+                changed.then(|| full_packet.encode_to_vec())
+            }
+            EDemoCommands::DemStringTables => {
+                let mut string_tables = CDemoStringTables::decode(msg)?;
+                self.aliases
+                    .anonymize_string_tables(&mut string_tables)
+                    .then(|| string_tables.encode_to_vec())
+            }
+            EDemoCommands::DemFileInfo => {
+                let mut file_info = CDemoFileInfo::decode(msg)?;
+                self.aliases
+                    .anonymize_file_info(&mut file_info)
+                    .then(|| file_info.encode_to_vec())
+            }
+            _ => None,
+        };
+
+        self.parser.on_demo_command(msg_type, msg)?;
+
+        Ok(rewritten)
+    }
+
+    fn rewrite_packet(&mut self, data: &[u8], apply: bool) -> Result<Option<Vec<u8>>, ParserError> {
+        let mut reader = Reader::new(data);
+        let mut out = BitWriter::default();
+        let mut changed = false;
+
+        while reader.bytes_remaining() != 0 {
+            let msg_type = reader.read_ubit_var();
+            let size = reader.read_var_u32();
+            let msg = reader.read_bytes(size);
+            if reader.is_overrun() {
+                return Err(std::io::Error::from(std::io::ErrorKind::UnexpectedEof).into());
+            }
+
+            let rewritten = self.rewrite_packet_message(msg_type as i32, &msg)?;
+            if apply {
+                self.parser.on_packet_message(msg_type as i32, &msg)?;
+            }
+
+            let msg = rewritten.inspect(|_| changed = true).unwrap_or(msg);
+            out.write_ubit_var(msg_type);
+            out.write_var_u32(msg.len() as u32);
+            out.write_bytes(&msg);
+        }
+
+        Ok(changed.then(|| out.into_bytes()))
+    }
+
+    fn rewrite_packet_message(
+        &mut self,
+        msg_type: i32,
+        msg: &[u8],
+    ) -> Result<Option<Vec<u8>>, ParserError> {
+        #[cfg(feature = "dota")]
+        if msg_type == EDotaUserMessages::DotaUmChatMessage as i32
+            && self.parser.context.game != Game::Deadlock
+        {
+            let mut chat = CDotaUserMsgChatMessage::decode(msg)?;
+            let text = self.aliases.replace_names(chat.message_text())?;
+            return Ok((text != chat.message_text()).then(|| {
+                chat.message_text = Some(text);
+                chat.encode_to_vec()
+            }));
+        }
+
+        match SvcMessages::try_from(msg_type) {
+            Ok(SvcMessages::SvcCreateStringTable) => {
+                let mut msg = CSvcMsgCreateStringTable::decode(msg)?;
+                if msg.name() != "userinfo" {
+                    return Ok(None);
+                }
+
+                let data = if msg.data_compressed() {
+                    snap::raw::Decoder::new().decompress_vec(msg.string_data())?
+                } else {
+                    msg.string_data().to_vec()
+                };
+                let table = StringTable {
+                    name: msg.name().into(),
+                    user_data_fixed_size: msg.user_data_fixed_size(),
+                    user_data_size: msg.user_data_size(),
+                    flags: msg.flags() as u32,
+                    var_int_bit_counts: msg.using_varint_bitcounts(),
+                    ..Default::default()
+                };
+
+                Ok(
+                    rewrite_userinfo(&table, &data, msg.num_entries(), &mut self.aliases)?.map(
+                        |data| {
+                            msg.uncompressed_size = Some(data.len() as i32);
+                            msg.string_data = Some(data);
+                            msg.data_compressed = Some(false);
+                            msg.encode_to_vec()
+                        },
+                    ),
+                )
+            }
+            Ok(SvcMessages::SvcUpdateStringTable) => {
+                let mut msg = CSvcMsgUpdateStringTable::decode(msg)?;
+                let Some(table) = self
+                    .parser
+                    .context
+                    .string_tables
+                    .tables
+                    .get(msg.table_id() as usize)
+                    .filter(|table| table.name() == "userinfo")
+                else {
+                    return Ok(None);
+                };
+
+                Ok(rewrite_userinfo(
+                    table,
+                    msg.string_data(),
+                    msg.num_changed_entries(),
+                    &mut self.aliases,
+                )?
+                .map(|data| {
+                    msg.string_data = Some(data);
+                    msg.encode_to_vec()
+                }))
+            }
+            Ok(SvcMessages::SvcPacketEntities) => {
+                let mut msg = CSvcMsgPacketEntities::decode(msg)?;
+                Ok(self.rewrite_entities(&msg)?.map(|data| {
+                    msg.entity_data = Some(data);
+                    msg.encode_to_vec()
+                }))
+            }
+            _ => Ok(None),
+        }
+    }
+
+    /// Walks entity data like `SvcMsg::packet_entities` without applying it
+    /// and replaces values of player fields.
+    fn rewrite_entities(
+        &mut self,
+        msg: &CSvcMsgPacketEntities,
+    ) -> Result<Option<Vec<u8>>, ParserError> {
+        let Anonymizer {
+            parser,
+            aliases,
+            player_classes,
+            ..
+        } = self;
+
+        let data = msg.entity_data();
+        let mut reader = Reader::new(data);
+        let mut replacements: Vec<(Range<usize>, Vec<u8>)> = vec![];
+        let mut created: HashMap<usize, Option<Arc<Class>>> = HashMap::default();
+
+        let mut index = usize::MAX;
+        for _ in 0..msg.updated_entries() {
+            index = index.wrapping_add((reader.read_ubit_var() + 1) as usize);
+
+            let cmd = reader.read_bits(2);
+            if cmd == 1 {
+                continue;
+            }
+
+            let class = match EntityEvents::from_cmd(cmd)? {
+                EntityEvents::Created => {
+                    let class_id = reader.read_bits(parser.context.classes.class_id_size);
+                    reader.read_bits(17);
+                    reader.read_var_u32();
+                    let class = parser
+                        .context
+                        .classes
+                        .get_by_id_arc(class_id as usize)?
+                        .clone();
+                    created.insert(index, Some(class.clone()));
+                    class
+                }
+                EntityEvents::Updated => created
+                    .get(&index)
+                    .cloned()
+                    .unwrap_or_else(|| {
+                        parser
+                            .context
+                            .entities
+                            .entities_vec
+                            .get(index)
+                            .and_then(|entity| entity.as_ref())
+                            .map(|entity| entity.class.clone())
+                    })
+                    .ok_or(EntityError::IndexNotFound(index))?,
+                EntityEvents::Deleted => {
+                    created.insert(index, None);
+                    continue;
+                }
+            };
+
+            let has_player_fields = *player_classes
+                .entry(class.id())
+                .or_insert_with(|| has_player_fields(&class.serializer));
+
+            parser
+                .field_reader
+                .visit_fields(&mut reader, &class.serializer, |fp, decoder, reader| {
+                    let start = reader.bit_position();
+                    let value = decoder.decode(reader);
+                    if !has_player_fields {
+                        return;
+                    }
+
+                    let name = class.serializer.get_name_for_field_path(fp);
+                    let name = name.rsplit('.').next().unwrap_or_default();
+
+                    let replacement = match (decoder, value) {
+                        (FieldDecoder::String, FieldValue::String(value))
+                            if NAME_FIELDS.contains(&name) =>
+                        {
+                            let alias = aliases.name(&value);
+                            if alias == value {
+                                return;
+                            }
+                            let mut buf = alias.into_bytes();
+                            buf.push(0);
+                            buf
+                        }
+                        (FieldDecoder::Unsigned64(properties), FieldValue::Unsigned64(value))
+                            if STEAM_ID_FIELDS.contains(&name) =>
+                        {
+                            let alias = aliases.steam_id(value);
+                            if alias == value {
+                                return;
+                            }
+                            if properties.encoder == Some(FieldEncoder::Fixed64) {
+                                alias.to_le_bytes().to_vec()
+                            } else {
+                                var_u64_bytes(alias)
+                            }
+                        }
+                        _ => return,
+                    };
+
+                    replacements.push((start..reader.bit_position(), replacement));
+                })
+                .map_err(|e| EntityError::CorruptedData(index, e))?;
+        }
+
+        if replacements.is_empty() {
+            return Ok(None);
+        }
+
+        let mut out = BitWriter::default();
+        let mut pos = 0;
+        for (range, replacement) in replacements {
+            out.write_bits_from(data, pos..range.start);
+            out.write_bytes(&replacement);
+            pos = range.end;
+        }
+        out.write_bits_from(data, pos..data.len() * 8);
+
+        Ok(Some(out.into_bytes()))
+    }
+}
+
+/// Rewrites [`CMsgPlayerInfo`] values of `userinfo` string table update.
+fn rewrite_userinfo(
+    table: &StringTable,
+    data: &[u8],
+    num_updates: i32,
+    aliases: &mut Aliases,
+) -> Result<Option<Vec<u8>>, ParserError> {
+    if table.user_data_fixed_size {
+        return Ok(None);
+    }
+
+    let mut values = vec![];
+    table.visit_values(data, num_updates, |range, value| {
+        values.push((range, value))
+    })?;
+
+    let mut out = BitWriter::default();
+    let mut pos = 0;
+    for (range, value) in values {
+        let Ok(mut info) = CMsgPlayerInfo::decode(value.as_slice()) else {
+            continue;
+        };
+        if !aliases.anonymize_player_info(&mut info) {
+            continue;
+        }
+
+        let value = info.encode_to_vec();
+        out.write_bits_from(data, pos..range.start);
+        if (table.flags & 0x1) != 0 {
+            out.write_bits(0, 1);
+        }
+        if table.var_int_bit_counts {
+            out.write_ubit_var(value.len() as u32);
+        } else {
+            out.write_bits(value.len() as u32, 17);
+        }
+        out.write_bytes(&value);
+        pos = range.end;
+    }
+
+    if pos == 0 {
+        return Ok(None);
+    }
+    out.write_bits_from(data, pos..data.len() * 8);
+
+    Ok(Some(out.into_bytes()))
+}
+
+fn has_player_fields(serializer: &Serializer) -> bool {
+    serializer.fields.iter().any(|field| {
+        NAME_FIELDS.contains(&field.var_name.as_ref())
+            || STEAM_ID_FIELDS.contains(&field.var_name.as_ref())
+            || match &field.model {
+                FieldModel::Vector(serializer) | FieldModel::Pointer(serializer) => {
+                    has_player_fields(serializer)
+                }
+                _ => false,
+            }
+    })
+}
+
+fn var_u64_bytes(mut x: u64) -> Vec<u8> {
+    let mut buf = Vec::with_capacity(10);
+    while x >= 0x80 {
+        buf.push(x as u8 | 0x80);
+        x >>= 7;
+    }
+    buf.push(x as u8);
+    buf
+}
+
+/// Player aliases by original name and Steam ID.
+#[derive(Default)]
+struct Aliases {
+    names: HashMap<String, usize>,
+    steam_ids: HashMap<u64, usize>,
+    players: usize,
+    /// Regex matching known names in chat and number of names it was built
+    /// from.
+    chat_regex: Option<Regex>,
+    chat_regex_names: usize,
+}
+
+impl Aliases {
+    /// Returns alias index of player, registering both name and Steam ID.
+    /// `None` if both are empty.
+    fn player(&mut self, name: &str, steam_id: u64) -> Option<usize> {
+        if name.is_empty() && steam_id == 0 {
+            return None;
+        }
+
+        let index = self
+            .steam_ids
+            .get(&steam_id)
+            .filter(|_| steam_id != 0)
+            .or_else(|| self.names.get(name))
+            .copied()
+            .unwrap_or_else(|| {
+                self.players += 1;
+                self.players
+            });
+
+        if steam_id != 0 {
+            self.steam_ids.insert(steam_id, index);
+        }
+        if !name.is_empty() {
+            self.names.insert(name.to_string(), index);
+        }
+
+        Some(index)
+    }
+
+    fn name(&mut self, name: &str) -> String {
+        self.player(name, 0)
+            .map_or_else(String::new, |index| format!("Player {index}"))
+    }
+
+    fn steam_id(&mut self, steam_id: u64) -> u64 {
+        self.player("", steam_id)
+            .map_or(0, |index| FIRST_STEAM_ID + index as u64)
+    }
+
+    /// Replaces whole-word mentions of known player names in text in a
+    /// single pass, longest names first.
+    #[cfg_attr(not(feature = "dota"), allow(dead_code))]
+    fn replace_names(&mut self, text: &str) -> Result<String, ParserError> {
+        if self.chat_regex_names != self.names.len() {
+            let mut names = self
+                .names
+                .keys()
+                .filter(|name| name.chars().count() >= MIN_CHAT_NAME_LEN)
+                .map(|name| regex::escape(name))
+                .collect::<Vec<_>>();
+            names.sort_by_key(|name| std::cmp::Reverse(name.len()));
+
+            self.chat_regex = match names.is_empty() {
+                true => None,
+                false => Some(Regex::new(&format!(
+                    r"\b{{start-half}}(?:{})\b{{end-half}}",
+                    names.join("|")
+                ))?),
+            };
+            self.chat_regex_names = self.names.len();
+        }
+
+        let Some(regex) = self.chat_regex.as_ref() else {
+            return Ok(text.to_string());
+        };
+        Ok(regex
+            .replace_all(text, |captures: &regex::Captures| {
+                format!("Player {}", self.names[&captures[0]])
+            })
+            .into_owned())
+    }
+
+    fn anonymize_player_info(&mut self, info: &mut CMsgPlayerInfo) -> bool {
+        let steam_id = if info.steamid() != 0 {
+            info.steamid()
+        } else {
+            info.xuid()
+        };
+        let Some(index) = self.player(info.name(), steam_id) else {
+            return false;
+        };
+
+        let before = info.clone();
+        if info.name.as_ref().is_some_and(|name| !name.is_empty()) {
+            info.name = Some(format!("Player {index}"));
+        }
+        if info.steamid() != 0 {
+            info.steamid = Some(FIRST_STEAM_ID + index as u64);
+        }
+        if info.xuid() != 0 {
+            info.xuid = Some(FIRST_STEAM_ID + index as u64);
+        }
+
+        *info != before
+    }
+
+    fn anonymize_string_tables(&mut self, string_tables: &mut CDemoStringTables) -> bool {
+        let mut changed = false;
+        for table in string_tables
+            .tables
+            .iter_mut()
+            .filter(|table| table.table_name() == "userinfo")
+        {
+            for item in table.items.iter_mut() {
+                let Ok(mut info) = CMsgPlayerInfo::decode(item.data()) else {
+                    continue;
+                };
+                if self.anonymize_player_info(&mut info) {
+                    item.data = Some(info.encode_to_vec());
+                    changed = true;
+                }
+            }
+        }
+        changed
+    }
+
+    fn anonymize_file_info(&mut self, file_info: &mut CDemoFileInfo) -> bool {
+        let Some(dota) = file_info
+            .game_info
+            .as_mut()
+            .and_then(|game_info| game_info.dota.as_mut())
+        else {
+            return false;
+        };
+
+        let mut changed = false;
+        for player in dota.player_info.iter_mut() {
+            let Some(index) = self.player(player.player_name(), player.steamid()) else {
+                continue;
+            };
+            if player
+                .player_name
+                .as_ref()
+                .is_some_and(|name| !name.is_empty())
+            {
+                player.player_name = Some(format!("Player {index}"));
+                changed = true;
+            }
+            if player.steamid() != 0 {
+                player.steamid = Some(FIRST_STEAM_ID + index as u64);
+                changed = true;
+            }
+        }
+        changed
+    }
+}
@@ -0,0 +1,435 @@
+use crate::entity::field::FieldState;
+use crate::error::{CheckpointError, ParserError};
+use crate::parser::Parser;
+use crate::string_table::{StringTable, StringTableRow};
+use crate::{Entity, FieldValue};
+use source2_demo_protobufs::prost::encoding::{decode_varint, encode_varint};
+use std::sync::Arc;
+
+#[cfg(feature = "dota")]
+use crate::proto::{CMsgDotaCombatLogEntry, Message};
+
+const MAGIC: &[u8; 8] = b"S2DEMCKP";
+const VERSION: u32 = 3;
+
+/// Features which change snapshot layout.
+const FEATURE_DOTA: u32 = 1;
+const FEATURES: u32 = if cfg!(feature = "dota") {
+    FEATURE_DOTA
+} else {
+    0
+};
+const MAX_FIELD_DEPTH: usize = 8;
+
+impl<'a> Parser<'a> {
+    /// Writes snapshot of the current parser state: read position, ticks,
+    /// string tables and entities. Classes, serializers and game event
+    /// definitions are not included, they are rebuilt from the replay by
+    /// [`Parser::restore`].
+    ///
+    /// Returns [`ParserError::SeekNotSupported`] for parsers created with
+    /// [`Parser::from_reader`], which can't be restored.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// use source2_demo::prelude::*;
+    ///
+    /// # fn main() -> Result<(), Box<dyn std::error::Error>> {
+    /// let replay = std::fs::read("match.dem")?;
+    ///
+    /// let mut parser = Parser::new(&replay)?;
+    /// parser.run_to_tick(30000)?;
+    /// std::fs::write("match.ckp", parser.checkpoint()?)?;
+    ///
+    /// let snapshot = std::fs::read("match.ckp")?;
+    /// let mut parser = Parser::restore(&replay, &snapshot)?;
+    /// parser.run_to_end()?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn checkpoint(&mut self) -> Result<Vec<u8>, ParserError> {
+        let replay_len = self
+            .reader
+            .replay_len()
+            .ok_or(ParserError::SeekNotSupported)?;
+
+        let mut w = SnapshotWriter::default();
+
+        w.buf.extend_from_slice(MAGIC);
+        w.u32(VERSION);
+        w.u32(FEATURES);
+        w.u64(replay_len as u64);
+        w.u32(self.replay_info.playback_ticks() as u32);
+
+        w.u64(self.reader.position() as u64);
+        w.u32(self.context.tick);
+        w.u32(self.context.previous_tick);
+        w.u32(self.context.net_tick);
+        w.u32(self.context.last_tick_end);
+        w.u32(self.context.last_full_packet_tick);
+        w.u32(self.context.game_build);
+
+        w.u64(self.context.string_tables.tables.len() as u64);
+        for table in self.context.string_tables.tables.iter() {
+            w.string_table(table);
+        }
+
+        w.u64(self.context.entities.iter().count() as u64);
+        for entity in self.context.entities.iter() {
+            w.u32(entity.index());
+            w.u32(entity.serial());
+            w.u32(entity.class.id as u32);
+            w.field_state(&entity.state);
+        }
+
+        #[cfg(feature = "dota")]
+        {
+            w.u64(self.combat_log.len() as u64);
+            for entry in self.combat_log.iter() {
+                w.bytes(&entry.encode_to_vec());
+            }
+        }
+
+        Ok(w.buf)
+    }
+
+    /// Creates parser positioned at the tick where snapshot created by
+    /// [`Parser::checkpoint`] was taken. Snapshot must be taken from the same
+    /// replay.
+    pub fn restore(replay: &'a [u8], snapshot: &[u8]) -> Result<Self, ParserError> {
+        let mut parser = Parser::new(replay)?;
+        let mut r = SnapshotReader { buf: snapshot };
+
+        if r.take(8)? != MAGIC {
+            return Err(CheckpointError::WrongMagic.into());
+        }
+
+        let version = r.u32()?;
+        if version != VERSION {
+            return Err(CheckpointError::UnsupportedVersion(version).into());
+        }
+
+        let features = r.u32()?;
+        if features != FEATURES {
+            return Err(CheckpointError::FeatureMismatch {
+                snapshot: features,
+                build: FEATURES,
+            }
+            .into());
+        }
+
+        if r.u64()? != replay.len() as u64 || r.u32()? != parser.replay_info.playback_ticks() as u32
+        {
+            return Err(CheckpointError::ReplayMismatch.into());
+        }
+
+        let offset = r.u64()? as usize;
+        if offset < 16 || offset > replay.len() {
+            return Err(CheckpointError::ReplayMismatch.into());
+        }
+
+        parser.prologue()?;
+        parser.reader.reset_to(offset)?;
+
+        let context = &mut parser.context;
+        context.tick = r.u32()?;
+        context.previous_tick = r.u32()?;
+        context.net_tick = r.u32()?;
+        context.last_tick_end = r.u32()?;
+        context.last_full_packet_tick = r.u32()?;
+        context.game_build = r.u32()?;
+
+        context.string_tables.tables.clear();
+        context.string_tables.name_to_table.clear();
+        context.baselines.baselines.clear();
+        context.baselines.states.clear();
+        for _ in 0..r.u64()? {
+            let table = r.string_table()?;
+            if table.name == "instancebaseline" {
+                for row in table.items.iter() {
+                    if let (Ok(id), Some(value)) = (row.key.parse(), row.value.as_ref()) {
+                        context.baselines.add_baseline(id, value.clone());
+                    }
+                }
+            }
+            context
+                .string_tables
+                .name_to_table
+                .insert(table.name.clone(), context.string_tables.tables.len());
+            context.string_tables.tables.push(table);
+        }
+
+        context.entities.clear();
+        for _ in 0..r.u64()? {
+            let index = r.u32()?;
+            let serial = r.u32()?;
+            let class_id = r.u32()? as usize;
+            let class = context
+                .classes
+                .classes_vec
+                .get(class_id)
+                .ok_or(CheckpointError::ClassNotFound(class_id as i32))?
+                .clone();
+            let state = r.field_state(0)?;
+            if index as usize >= context.entities.entities_vec.len() {
+                return Err(CheckpointError::Corrupted.into());
+            }
+            context
+                .entities
+                .insert(index as usize, Entity::new(index, serial, class, state));
+        }
+
+        #[cfg(feature = "dota")]
+        for _ in 0..r.u64()? {
+            let entry = CMsgDotaCombatLogEntry::decode(r.bytes()?)
+                .map_err(|_| CheckpointError::Corrupted)?;
+            parser.combat_log.push_back(entry);
+        }
+
+        if !r.buf.is_empty() {
+            return Err(CheckpointError::TrailingData(r.buf.len()).into());
+        }
+
+        Ok(parser)
+    }
+}
+
+#[derive(Default)]
+struct SnapshotWriter {
+    buf: Vec<u8>,
+}
+
+impl SnapshotWriter {
+    fn u64(&mut self, x: u64) {
+        encode_varint(x, &mut self.buf);
+    }
+
+    fn u32(&mut self, x: u32) {
+        encode_varint(x as u64, &mut self.buf);
+    }
+
+    fn i64(&mut self, x: i64) {
+        self.u64(((x << 1) ^ (x >> 63)) as u64);
+    }
+
+    fn f32(&mut self, x: f32) {
+        self.buf.extend_from_slice(&x.to_le_bytes());
+    }
+
+    fn bytes(&mut self, x: &[u8]) {
+        self.u64(x.len() as u64);
+        self.buf.extend_from_slice(x);
+    }
+
+    fn string_table(&mut self, table: &StringTable) {
+        self.u32(table.index as u32);
+        self.bytes(table.name.as_bytes());
+        self.u32(table.user_data_fixed_size as u32);
+        self.u32(table.user_data_size as u32);
+        self.u32(table.flags);
+        self.u32(table.var_int_bit_counts as u32);
+
+        self.u64(table.keys.len() as u64);
+        for key in table.keys.iter() {
+            self.bytes(key.as_bytes());
+        }
+
+        self.u64(table.items.len() as u64);
+        for row in table.items.iter() {
+            self.u32(row.index as u32);
+            self.bytes(row.key.as_bytes());
+            match row.value.as_ref() {
+                Some(value) => {
+                    self.u32(1);
+                    self.bytes(value);
+                }
+                None => self.u32(0),
+            }
+        }
+    }
+
+    fn field_state(&mut self, state: &FieldState) {
+        match state.value.as_ref() {
+            None => self.u32(0),
+            Some(FieldValue::Boolean(x)) => {
+                self.u32(1);
+                self.u32(*x as u32);
+            }
+            Some(FieldValue::String(x)) => {
+                self.u32(2);
+                self.bytes(x.as_bytes());
+            }
+            Some(FieldValue::Float(x)) => {
+                self.u32(3);
+                self.f32(*x);
+            }
+            Some(FieldValue::Vector2D(x)) => {
+                self.u32(4);
+                x.iter().for_each(|&v| self.f32(v));
+            }
+            Some(FieldValue::Vector3D(x)) => {
+                self.u32(5);
+                x.iter().for_each(|&v| self.f32(v));
+            }
+            Some(FieldValue::Vector4D(x)) => {
+                self.u32(6);
+                x.iter().for_each(|&v| self.f32(v));
+            }
+            Some(FieldValue::Signed8(x)) => {
+                self.u32(7);
+                self.i64(*x as i64);
+            }
+            Some(FieldValue::Signed16(x)) => {
+                self.u32(8);
+                self.i64(*x as i64);
+            }
+            Some(FieldValue::Signed32(x)) => {
+                self.u32(9);
+                self.i64(*x as i64);
+            }
+            Some(FieldValue::Signed64(x)) => {
+                self.u32(10);
+                self.i64(*x);
+            }
+            Some(FieldValue::Unsigned8(x)) => {
+                self.u32(11);
+                self.u64(*x as u64);
+            }
+            Some(FieldValue::Unsigned16(x)) => {
+                self.u32(12);
+                self.u64(*x as u64);
+            }
+            Some(FieldValue::Unsigned32(x)) => {
+                self.u32(13);
+                self.u64(*x as u64);
+            }
+            Some(FieldValue::Unsigned64(x)) => {
+                self.u32(14);
+                self.u64(*x);
+            }
+        }
+
+        self.u64(state.vec.len() as u64);
+        for child in state.vec.iter() {
+            self.field_state(child);
+        }
+    }
+}
+
+struct SnapshotReader<'b> {
+    buf: &'b [u8],
+}
+
+impl<'b> SnapshotReader<'b> {
+    fn take(&mut self, n: usize) -> Result<&'b [u8], CheckpointError> {
+        if self.buf.len() < n {
+            return Err(CheckpointError::Truncated);
+        }
+        let (head, tail) = self.buf.split_at(n);
+        self.buf = tail;
+        Ok(head)
+    }
+
+    fn u64(&mut self) -> Result<u64, CheckpointError> {
+        decode_varint(&mut self.buf).map_err(|_| CheckpointError::Truncated)
+    }
+
+    fn u32(&mut self) -> Result<u32, CheckpointError> {
+        self.u64()?
+            .try_into()
+            .map_err(|_| CheckpointError::Corrupted)
+    }
+
+    fn i64(&mut self) -> Result<i64, CheckpointError> {
+        let x = self.u64()?;
+        Ok((x >> 1) as i64 ^ -((x & 1) as i64))
+    }
+
+    fn f32(&mut self) -> Result<f32, CheckpointError> {
+        Ok(f32::from_le_bytes(self.take(4)?.try_into().unwrap()))
+    }
+
+    fn f32_array<const N: usize>(&mut self) -> Result<[f32; N], CheckpointError> {
+        let mut x = [0.0; N];
+        for v in x.iter_mut() {
+            *v = self.f32()?;
+        }
+        Ok(x)
+    }
+
+    fn bytes(&mut self) -> Result<&'b [u8], CheckpointError> {
+        let len = self.u64()?;
+        self.take(len.try_into().map_err(|_| CheckpointError::Truncated)?)
+    }
+
+    fn string(&mut self) -> Result<String, CheckpointError> {
+        String::from_utf8(self.bytes()?.to_vec()).map_err(|_| CheckpointError::Corrupted)
+    }
+
+    fn string_table(&mut self) -> Result<StringTable, CheckpointError> {
+        let mut table = StringTable {
+            index: self.u32()? as i32,
+            name: self.string()?,
+            user_data_fixed_size: self.u32()? != 0,
+            user_data_size: self.u32()? as i32,
+            flags: self.u32()?,
+            var_int_bit_counts: self.u32()? != 0,
+            ..Default::default()
+        };
+
+        let mut keys = (0..self.u64()?)
+            .map(|_| self.string())
+            .collect::<Result<Vec<_>, _>>()?;
+        keys.resize(32, String::default());
+        table.keys = keys;
+
+        for _ in 0..self.u64()? {
+            let index = self.u32()? as i32;
+            let key = self.string()?;
+            let value = match self.u32()? {
+                0 => None,
+                _ => Some(Arc::new(self.bytes()?.to_vec())),
+            };
+            table.items.push(StringTableRow::new(index, key, value));
+        }
+
+        Ok(table)
+    }
+
+    fn field_state(&mut self, depth: usize) -> Result<FieldState, CheckpointError> {
+        if depth > MAX_FIELD_DEPTH {
+            return Err(CheckpointError::Corrupted);
+        }
+
+        let value = match self.u32()? {
+            0 => None,
+            1 => Some(FieldValue::Boolean(self.u32()? != 0)),
+            2 => Some(FieldValue::String(self.string()?)),
+            3 => Some(FieldValue::Float(self.f32()?)),
+            4 => Some(FieldValue::Vector2D(self.f32_array()?)),
+            5 => Some(FieldValue::Vector3D(self.f32_array()?)),
+            6 => Some(FieldValue::Vector4D(self.f32_array()?)),
+            7 => Some(FieldValue::Signed8(self.i64()? as i8)),
+            8 => Some(FieldValue::Signed16(self.i64()? as i16)),
+            9 => Some(FieldValue::Signed32(self.i64()? as i32)),
+            10 => Some(FieldValue::Signed64(self.i64()?)),
+            11 => Some(FieldValue::Unsigned8(self.u64()? as u8)),
+            12 => Some(FieldValue::Unsigned16(self.u64()? as u16)),
+            13 => Some(FieldValue::Unsigned32(self.u64()? as u32)),
+            14 => Some(FieldValue::Unsigned64(self.u64()?)),
+            _ => return Err(CheckpointError::Corrupted),
+        };
+
+        let len = self.u64()?;
+        if len > self.buf.len() as u64 {
+            return Err(CheckpointError::Truncated);
+        }
+
+        let vec = (0..len)
+            .map(|_| self.field_state(depth + 1))
+            .collect::<Result<Vec<_>, _>>()?;
+
+        Ok(FieldState { vec, value })
+    }
+}
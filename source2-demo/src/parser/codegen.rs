@@ -0,0 +1,338 @@
+use crate::entity::field::{Field, FieldDecoder, FieldModel, FieldType, Serializer};
+use crate::error::ParserError;
+use crate::{Class, Classes, Parser};
+use hashbrown::HashSet;
+use std::fmt::Write;
+
+/// Generates Rust accessor structs for entity classes from send tables of
+/// reference replay, so property names and types are checked at compile
+/// time.
+///
+/// Every class gets unit struct with `CLASS_NAME` constant and method per
+/// property returning `Option` of its Rust type, e.g.
+/// `CDotaUnitHeroAxe::health(&Entity) -> Option<i32>` for `m_iHealth`.
+/// Elements of arrays and vectors take index arguments. Field paths differ
+/// between game builds, so every accessor holds a static
+/// [`PropertyAccessor`](crate::PropertyAccessor) which resolves property
+/// name to field path on the first call for each class. Further calls fill
+/// index arguments into the cached path and read the value by it.
+///
+/// # Examples
+///
+/// Generating accessors in `build.rs`:
+///
+/// ```no_run
+/// use source2_demo::CodeGenerator;
+///
+/// # fn main() -> Result<(), Box<dyn std::error::Error>> {
+/// let replay = std::fs::read("reference.dem")?;
+/// let code = CodeGenerator::new(&replay)?.generate(&["CDOTA_Unit_Hero_*", "CDOTAPlayerController"]);
+/// std::fs::write(format!("{}/entities.rs", std::env::var("OUT_DIR")?), code)?;
+/// # Ok(())
+/// # }
+/// ```
+///
+/// And using them:
+///
+/// ```ignore
+/// mod entities {
+///     include!(concat!(env!("OUT_DIR"), "/entities.rs"));
+/// }
+///
+/// let health = entities::CDotaUnitHeroAxe::health(entity);
+/// ```
+pub struct CodeGenerator {
+    classes: Classes,
+    game_build: u32,
+}
+
+impl CodeGenerator {
+    pub fn new(replay: &[u8]) -> Result<Self, ParserError> {
+        let mut parser = Parser::new(replay)?;
+        parser.prologue()?;
+
+        Ok(CodeGenerator {
+            classes: parser.context.classes.clone(),
+            game_build: parser.context.game_build,
+        })
+    }
+
+    /// Returns Rust source with accessor structs for classes matching any of
+    /// given glob patterns ([`Class::matches`]).
+    pub fn generate(&self, patterns: &[&str]) -> String {
+        let mut out = format!(
+            "// Generated by source2-demo from replay of build {}. Do not edit.\n",
+            self.game_build
+        );
+
+        let mut structs = HashSet::new();
+        for class in self
+            .classes
+            .iter()
+            .filter(|class| patterns.iter().any(|pattern| class.matches(pattern)))
+        {
+            let name = unique(&mut structs, struct_name(class.name()), || {
+                class
+                    .name()
+                    .replace(|c: char| !c.is_ascii_alphanumeric(), "")
+            });
+            write_class(&mut out, class, &name);
+        }
+
+        out
+    }
+}
+
+/// Property accessor, `name` contains `{:04}` for every index argument.
+struct Accessor<'a> {
+    name: String,
+    suffix: &'static str,
+    indices: usize,
+    rust_type: String,
+    field_type: &'a FieldType,
+}
+
+fn write_class(out: &mut String, class: &Class, name: &str) {
+    let mut accessors = vec![];
+    collect_accessors(&class.serializer, "", 0, &mut accessors);
+
+    let _ = writeln!(out);
+    let _ = writeln!(out, "/// `{}`", class.name());
+    let _ = writeln!(out, "#[allow(dead_code)]");
+    let _ = writeln!(out, "pub struct {name};");
+    let _ = writeln!(out);
+    let _ = writeln!(out, "#[allow(dead_code)]");
+    let _ = writeln!(out, "impl {name} {{");
+    let _ = writeln!(
+        out,
+        "    pub const CLASS_NAME: &str = \"{}\";",
+        class.name()
+    );
+
+    let mut methods = HashSet::new();
+    for accessor in accessors {
+        let method = unique(
+            &mut methods,
+            method_name(&accessor.name, true) + accessor.suffix,
+            || method_name(&accessor.name, false) + accessor.suffix,
+        );
+        let args = (0..accessor.indices)
+            .map(|i| format!(", i{i}: usize"))
+            .collect::<String>();
+        let indices = (0..accessor.indices)
+            .map(|i| format!("i{i}"))
+            .collect::<Vec<_>>()
+            .join(", ");
+
+        let _ = writeln!(out);
+        let _ = writeln!(
+            out,
+            "    /// `{}`: `{}`",
+            accessor.name, accessor.field_type
+        );
+        let _ = writeln!(
+            out,
+            "    pub fn {method}(entity: &::source2_demo::Entity{args}) -> Option<{}> {{",
+            accessor.rust_type
+        );
+        let _ = writeln!(
+            out,
+            "        static PROPERTY: ::source2_demo::PropertyAccessor =\n            ::source2_demo::PropertyAccessor::new({:?});",
+            accessor.name
+        );
+        let _ = writeln!(
+            out,
+            "        PROPERTY.get(entity, &[{indices}])?.try_into().ok()"
+        );
+        let _ = writeln!(out, "    }}");
+    }
+
+    let _ = writeln!(out, "}}");
+}
+
+fn collect_accessors<'a>(
+    serializer: &'a Serializer,
+    prefix: &str,
+    indices: usize,
+    out: &mut Vec<Accessor<'a>>,
+) {
+    for field in serializer.fields.iter() {
+        let name = format!("{prefix}{}", field.var_name);
+        let field_type = field.field_type.as_ref();
+        match &field.model {
+            FieldModel::Value => out.push(Accessor {
+                rust_type: rust_type(&field.decoder, field_type),
+                name,
+                suffix: "",
+                indices,
+                field_type,
+            }),
+            FieldModel::Array => out.push(Accessor {
+                name: format!("{name}.{{:04}}"),
+                suffix: "",
+                indices: indices + 1,
+                rust_type: rust_type(&field.decoder, field_type),
+                field_type,
+            }),
+            FieldModel::ArrayVector(decoder) => {
+                let element_type = field_type.generic.as_deref().unwrap_or(field_type);
+                out.push(Accessor {
+                    name: format!("{name}.{{:04}}"),
+                    suffix: "",
+                    indices: indices + 1,
+                    rust_type: rust_type(decoder, element_type),
+                    field_type: element_type,
+                });
+                out.push(length_accessor(name, indices, field));
+            }
+            FieldModel::Vector(serializer) => {
+                collect_accessors(serializer, &format!("{name}.{{:04}}."), indices + 1, out);
+                out.push(length_accessor(name, indices, field));
+            }
+            FieldModel::Pointer(serializer) => {
+                collect_accessors(serializer, &format!("{name}."), indices, out);
+            }
+        }
+    }
+}
+
+/// Accessor of vector field itself, which holds number of elements.
+fn length_accessor(name: String, indices: usize, field: &Field) -> Accessor<'_> {
+    Accessor {
+        name,
+        suffix: "_len",
+        indices,
+        rust_type: field.decoder.to_string(),
+        field_type: &field.field_type,
+    }
+}
+
+fn rust_type(decoder: &FieldDecoder, field_type: &FieldType) -> String {
+    if field_type.base.as_ref() == "CHandle" {
+        "::source2_demo::EntityHandle".to_string()
+    } else {
+        decoder.to_string()
+    }
+}
+
+/// Returns `name` if it's not taken yet, otherwise `fallback` or numbered
+/// `name`.
+fn unique(taken: &mut HashSet<String>, name: String, fallback: impl FnOnce() -> String) -> String {
+    let name = if !taken.contains(&name) {
+        name
+    } else {
+        let fallback = fallback();
+        if !taken.contains(&fallback) {
+            fallback
+        } else {
+            (2..)
+                .map(|i| format!("{name}_{i}"))
+                .find(|x| !taken.contains(x))
+                .unwrap()
+        }
+    };
+    taken.insert(name.clone());
+    name
+}
+
+/// `CDOTA_Unit_Hero_Axe` -> `CDotaUnitHeroAxe`.
+fn struct_name(class_name: &str) -> String {
+    let (mut name, rest) = match strip_class_prefix(class_name) {
+        Some(rest) => ("C".to_string(), rest),
+        None => (String::new(), class_name),
+    };
+    for word in snake_case(rest).split('_') {
+        let mut chars = word.chars();
+        if let Some(first) = chars.next() {
+            name.push(first.to_ascii_uppercase());
+            name += chars.as_str();
+        }
+    }
+    if !name.starts_with(|c: char| c.is_ascii_alphabetic()) {
+        name.insert(0, 'C');
+    }
+    name
+}
+
+/// Strips `C` of class names such as `CBodyComponent`.
+fn strip_class_prefix(name: &str) -> Option<&str> {
+    name.strip_prefix('C')
+        .filter(|rest| rest.starts_with(|c: char| c.is_ascii_uppercase()))
+}
+
+/// Type prefixes of Hungarian notation, longest first.
+const PREFIXES: &[&str] = &[
+    "isz", "str", "vec", "ang", "clr", "sz", "fl", "un", "bv", "n", "i", "b", "h", "e", "u", "f",
+    "p",
+];
+
+/// `m_vecPlayerData.{:04}.m_iszPlayerName` -> `player_data_player_name`.
+fn method_name(property: &str, strip_prefixes: bool) -> String {
+    let mut name = property
+        .split('.')
+        .filter(|part| *part != "{:04}")
+        .map(|part| {
+            let part = part
+                .strip_prefix("m_")
+                .or_else(|| strip_class_prefix(part))
+                .unwrap_or(part);
+            let part = match strip_prefixes {
+                true => PREFIXES
+                    .iter()
+                    .find_map(|prefix| {
+                        part.strip_prefix(prefix)
+                            .filter(|rest| rest.starts_with(|c: char| c.is_ascii_uppercase()))
+                    })
+                    .unwrap_or(part),
+                false => part,
+            };
+            snake_case(part)
+        })
+        .filter(|part| !part.is_empty())
+        .collect::<Vec<_>>()
+        .join("_");
+
+    if name.is_empty() {
+        name = "value".to_string();
+    }
+    if name.starts_with(|c: char| c.is_ascii_digit()) {
+        name.insert(0, '_');
+    }
+    if KEYWORDS.contains(&name.as_str()) {
+        name.push('_');
+    }
+    name
+}
+
+fn snake_case(s: &str) -> String {
+    let chars = s.chars().collect::<Vec<_>>();
+    let mut out = String::new();
+    for (i, &c) in chars.iter().enumerate() {
+        if !c.is_ascii_alphanumeric() {
+            if !out.is_empty() && !out.ends_with('_') {
+                out.push('_');
+            }
+            continue;
+        }
+        if c.is_ascii_uppercase() && i > 0 && !out.ends_with('_') {
+            let prev = chars[i - 1];
+            let next_lower = chars.get(i + 1).is_some_and(|x| x.is_ascii_lowercase());
+            if prev.is_ascii_lowercase()
+                || prev.is_ascii_digit()
+                || (prev.is_ascii_uppercase() && next_lower)
+            {
+                out.push('_');
+            }
+        }
+        out.push(c.to_ascii_lowercase());
+    }
+    out.trim_end_matches('_').to_string()
+}
+
+const KEYWORDS: &[&str] = &[
+    "abstract", "as", "async", "await", "become", "box", "break", "const", "continue", "crate",
+    "do", "dyn", "else", "enum", "extern", "false", "final", "fn", "for", "gen", "if", "impl",
+    "in", "let", "loop", "macro", "match", "mod", "move", "mut", "override", "priv", "pub", "ref",
+    "return", "self", "static", "struct", "super", "trait", "true", "try", "type", "typeof",
+    "unsafe", "unsized", "use", "virtual", "where", "while", "yield",
+];
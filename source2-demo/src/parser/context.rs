@@ -1,11 +1,13 @@
 use crate::entity::field::*;
 use crate::entity::*;
 use crate::event::*;
+use crate::parser::Game;
 use crate::string_table::*;
 use hashbrown::HashMap;
-use std::rc::Rc;
+use std::sync::Arc;
 
 /// Current replay state.
+#[derive(Clone)]
 pub struct Context {
     pub(crate) classes: Classes,
     pub(crate) entities: Entities,
@@ -15,14 +17,24 @@ pub struct Context {
     pub(crate) tick: u32,
     pub(crate) previous_tick: u32,
     pub(crate) net_tick: u32,
+    pub(crate) last_tick_end: u32,
+    pub(crate) tick_interval: f32,
 
+    pub(crate) game: Game,
     pub(crate) game_build: u32,
 
     pub(crate) baselines: BaselineContainer,
-    pub(crate) serializers: HashMap<Box<str>, Rc<Serializer>>,
+    pub(crate) serializers: HashMap<Box<str>, Arc<Serializer>>,
     pub(crate) last_full_packet_tick: u32,
+
+    pub(crate) property_changes: Vec<PropertyChange>,
 }
 
+const _: () = {
+    const fn assert_send_sync<T: Send + Sync>() {}
+    assert_send_sync::<Context>();
+};
+
 impl Default for Context {
     fn default() -> Self {
         Context {
@@ -33,10 +45,14 @@ impl Default for Context {
             tick: u32::MAX,
             previous_tick: u32::MAX,
             net_tick: u32::MAX,
+            last_tick_end: u32::MAX,
+            tick_interval: 1.0 / 30.0,
+            game: Game::Unknown,
             game_build: 0,
             baselines: BaselineContainer::default(),
             serializers: HashMap::default(),
             last_full_packet_tick: u32::MAX,
+            property_changes: Vec::new(),
         }
     }
 }
@@ -58,6 +74,59 @@ impl Context {
         &self.game_events
     }
 
+    /// Properties written by creation or update of entity passed to current
+    /// [`Observer::on_entity`](crate::Observer::on_entity) call. Empty in
+    /// other callbacks or if no observer requested
+    /// [`Observer::property_tracking`](crate::Observer::property_tracking).
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// use source2_demo::prelude::*;
+    ///
+    /// #[derive(Default)]
+    /// struct Gold;
+    ///
+    /// impl Observer for Gold {
+    ///     fn on_entity(
+    ///         &mut self,
+    ///         ctx: &Context,
+    ///         event: EntityEvents,
+    ///         entity: &Entity,
+    ///     ) -> ObserverResult {
+    ///         for change in ctx.property_changes() {
+    ///             println!("{}: {:?} -> {:?}", change.name(entity), change.previous(), change.value());
+    ///         }
+    ///         Ok(())
+    ///     }
+    ///
+    ///     fn property_tracking(&self) -> PropertyTracking {
+    ///         PropertyTracking::Values
+    ///     }
+    /// }
+    /// ```
+    ///
+    /// With `#[observer]` macro changes can be added as the last argument of
+    /// `#[on_entity]` method.
+    ///
+    /// ```no_run
+    /// use source2_demo::prelude::*;
+    ///
+    /// #[derive(Default)]
+    /// struct Gold;
+    ///
+    /// #[observer]
+    /// impl Gold {
+    ///     #[on_entity("CDOTA_DataRadiant")]
+    ///     fn data(&mut self, entity: &Entity, changes: &[PropertyChange]) -> ObserverResult {
+    ///         Ok(())
+    ///     }
+    /// }
+    /// ```
+    pub fn property_changes(&self) -> &[PropertyChange] {
+        &self.property_changes
+    }
+
     pub fn tick(&self) -> u32 {
         self.tick
     }
@@ -66,6 +135,59 @@ impl Context {
         self.net_tick
     }
 
+    /// Duration of tick in seconds from server info, `1 / 30` by default.
+    pub fn tick_interval(&self) -> f32 {
+        self.tick_interval
+    }
+
+    /// Returns `true` in [`Observer::on_tick_end`](crate::Observer::on_tick_end)
+    /// if a multiple of `ticks` was reached since the previous tick end.
+    /// Used by `#[on_tick_interval(n)]`.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// use source2_demo::prelude::*;
+    ///
+    /// #[derive(Default)]
+    /// struct Sampler;
+    ///
+    /// #[observer]
+    /// impl Sampler {
+    ///     #[on_tick_interval(30)]
+    ///     fn every_30_ticks(&mut self, ctx: &Context) -> ObserverResult {
+    ///         Ok(())
+    ///     }
+    ///
+    ///     #[on_interval(seconds = 0.5)]
+    ///     fn twice_a_second(&mut self) -> ObserverResult {
+    ///         Ok(())
+    ///     }
+    /// }
+    /// ```
+    pub fn is_tick_interval(&self, ticks: u32) -> bool {
+        self.crossed_interval(ticks as f64)
+    }
+
+    /// Same as [`Context::is_tick_interval`] with interval in seconds.
+    /// Used by `#[on_interval(seconds = ..)]`.
+    pub fn is_time_interval(&self, seconds: f32) -> bool {
+        self.crossed_interval(seconds as f64 / self.tick_interval as f64)
+    }
+
+    fn crossed_interval(&self, ticks: f64) -> bool {
+        if ticks <= 0.0 || self.last_tick_end == u32::MAX {
+            return true;
+        }
+        (self.tick as f64 / ticks).floor() != (self.last_tick_end as f64 / ticks).floor()
+    }
+
+    /// Game detected from replay, [`Game::Unknown`] until file header or
+    /// server info is parsed.
+    pub fn game(&self) -> Game {
+        self.game
+    }
+
     pub fn game_build(&self) -> u32 {
         self.game_build
     }
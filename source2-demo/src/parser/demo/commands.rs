@@ -1,20 +1,26 @@
 use crate::entity::field::*;
 use crate::entity::Class;
-use crate::error::ParserError;
+use crate::error::{ParserError, StringTableError};
 use crate::parser::demo::DemoMessages;
 use crate::proto::*;
 use crate::reader::*;
-use crate::{Parser, StringTableRow};
+use crate::parser::{Game, ReplayMessage};
+use crate::{try_observers, Parser, StringTableRow};
 use hashbrown::HashMap;
-use std::rc::Rc;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
 
 pub trait DemoCommands {
+    fn dem_file_header(&mut self, file_header: CDemoFileHeader) -> Result<(), ParserError>;
+
     fn dem_send_tables(&mut self, send_tables: CDemoSendTables) -> Result<(), ParserError>;
 
     fn dem_class_info(&mut self, class_info: CDemoClassInfo) -> Result<(), ParserError>;
 
     fn dem_packet(&mut self, demo_packet: CDemoPacket) -> Result<(), ParserError>;
 
+    fn on_packet_message(&mut self, msg_type: i32, msg: &[u8]) -> Result<(), ParserError>;
+
     fn dem_full_packet(&mut self, full_packet: CDemoFullPacket) -> Result<(), ParserError>;
 
     fn dem_string_tables(&mut self, string_tables: CDemoStringTables) -> Result<(), ParserError>;
@@ -22,10 +28,20 @@ pub trait DemoCommands {
     fn dem_stop(&mut self) -> Result<(), ParserError> {
         Ok(())
     }
+
+    fn dem_file_info(&mut self, file_info: CDemoFileInfo) -> Result<(), ParserError>;
 }
 
 impl DemoCommands for Parser<'_> {
+    fn dem_file_header(&mut self, file_header: CDemoFileHeader) -> Result<(), ParserError> {
+        if self.context.game == Game::Unknown {
+            self.context.game = Game::from_game_dir(file_header.game_directory());
+        }
+        Ok(())
+    }
+
     fn dem_send_tables(&mut self, send_tables: CDemoSendTables) -> Result<(), ParserError> {
+        let game = self.context.game;
         let serializers = &mut self.context.serializers;
 
         let mut reader = Reader::new(send_tables.data());
@@ -35,21 +51,19 @@ impl DemoCommands for Parser<'_> {
         let fs = CSvcMsgFlattenedSerializer::decode(buf.as_slice())?;
 
         let resolve = |p: Option<i32>| -> Box<str> {
-            if let Some(i) = p {
-                return fs.symbols[i as usize].clone().into();
-            }
-            "".into()
+            p.and_then(|i| fs.symbols.get(i as usize))
+                .map_or_else(|| "".into(), |symbol| symbol.as_str().into())
         };
 
-        let mut fields: Vec<Rc<Field>> = vec![];
-        let mut field_types: HashMap<Box<str>, Rc<FieldType>> = HashMap::default();
+        let mut fields: Vec<Arc<Field>> = vec![];
+        let mut field_types: HashMap<Box<str>, Arc<FieldType>> = HashMap::default();
 
         for s in fs.serializers.iter() {
-            let serializer_name = fs.symbols[s.serializer_name_sym() as usize].clone();
+            let serializer_name = resolve(Some(s.serializer_name_sym()));
             let mut serializer = Serializer::default();
 
             for i in s.fields_index.iter().map(|x| *x as usize) {
-                let current_field = &fs.fields[i];
+                let current_field = fs.fields.get(i).ok_or(ParserError::ReplayEncodingError)?;
                 let field_serializer_name = resolve(current_field.field_serializer_name_sym);
 
                 if i >= fields.len() {
@@ -76,9 +90,11 @@ impl DemoCommands for Parser<'_> {
                     };
 
                     let model = if field_type.pointer {
-                        FieldModel::Pointer(current_field_serializer.unwrap())
-                    } else if current_field_serializer.is_some() {
-                        FieldModel::Vector(current_field_serializer.unwrap())
+                        FieldModel::Pointer(current_field_serializer.ok_or_else(|| {
+                            ParserError::SerializerNotFound(field_serializer_name.to_string())
+                        })?)
+                    } else if let Some(serializer) = current_field_serializer {
+                        FieldModel::Vector(serializer)
                     } else if [
                         "CUtlVector",
                         "CNetworkUtlVectorBase",
@@ -87,8 +103,9 @@ impl DemoCommands for Parser<'_> {
                     .contains(&field_type.base.as_ref())
                     {
                         FieldModel::ArrayVector(FieldDecoder::from_field(
-                            field_type.generic.as_ref().unwrap(),
+                            field_type.generic.as_deref().unwrap_or(&field_type),
                             properties,
+                            game,
                         ))
                     } else if field_type.count.is_some_and(|x| x > 0)
                         && field_type.base.as_ref() != "char"
@@ -97,10 +114,10 @@ impl DemoCommands for Parser<'_> {
                     } else {
                         FieldModel::Value
                     };
-                    
+
                     let decoder = match model {
                         FieldModel::Value | FieldModel::Array => {
-                            FieldDecoder::from_field(&field_type, properties)
+                            FieldDecoder::from_field(&field_type, properties, game)
                         }
                         FieldModel::Vector(_) | FieldModel::ArrayVector(_) => FieldDecoder::Unsigned32,
                         FieldModel::Pointer(_) => FieldDecoder::Boolean,
@@ -115,9 +132,14 @@ impl DemoCommands for Parser<'_> {
                     };
                     fields.push(field.into());
                 }
-                serializer.fields.push(fields[i].clone());
+                serializer.fields.push(
+                    fields
+                        .get(i)
+                        .ok_or(ParserError::ReplayEncodingError)?
+                        .clone(),
+                );
             }
-            serializers.insert(serializer_name.into(), serializer.into());
+            serializers.insert(serializer_name, serializer.into());
         }
         Ok(())
     }
@@ -126,8 +148,13 @@ impl DemoCommands for Parser<'_> {
         for class in class_info.classes {
             let class_id = class.class_id();
             let network_name = class.network_name();
-            let serializer = self.context.serializers[network_name].clone();
-            let class = Rc::new(Class::new(class_id, network_name.into(), serializer));
+            let serializer = self
+                .context
+                .serializers
+                .get(network_name)
+                .ok_or_else(|| ParserError::SerializerNotFound(network_name.to_string()))?
+                .clone();
+            let class = Arc::new(Class::new(class_id, network_name.into(), serializer));
 
             self.context.classes.classes_vec.push(class.clone());
             self.context
@@ -144,40 +171,68 @@ impl DemoCommands for Parser<'_> {
             let msg_type = packet_reader.read_ubit_var() as i32;
             let size = packet_reader.read_var_u32();
             let msg_buf = packet_reader.read_bytes(size);
+            if packet_reader.is_overrun() {
+                return Err(std::io::Error::from(std::io::ErrorKind::UnexpectedEof).into());
+            }
 
-            #[cfg(feature = "dota")]
-            if let Ok(msg) = EDotaUserMessages::try_from(msg_type) {
-                self.on_dota_user_message(msg, &msg_buf)?;
-                continue;
+            let started = self.stats.is_some().then(Instant::now);
+            self.on_packet_message(msg_type, &msg_buf)?;
+            if let (Some(stats), Some(started)) = (self.stats.as_mut(), started) {
+                if let Some(msg_type) = ReplayMessage::from_packet_message(msg_type, self.context.game) {
+                    stats.add_message(msg_type, msg_buf.len(), started.elapsed());
+                }
             }
+        }
 
-            #[cfg(feature = "deadlock")]
-            if let Ok(msg) = CitadelUserMessageIds::try_from(msg_type) {
-                self.on_citadel_user_message(msg, &msg_buf)?;
-                continue;
-            } else if let Ok(msg) = ECitadelGameEvents::try_from(msg_type) {
-                self.on_citadel_game_event(msg, &msg_buf)?;
-                continue;
+        Ok(())
+    }
+
+    fn on_packet_message(&mut self, msg_type: i32, msg: &[u8]) -> Result<(), ParserError> {
+        #[cfg(feature = "dota")]
+        if self.context.game != Game::Deadlock {
+            if let Ok(msg_type) = EDotaUserMessages::try_from(msg_type) {
+                return self.on_dota_user_message(msg_type, msg);
             }
+        }
 
-            if let Ok(msg) = SvcMessages::try_from(msg_type) {
-                self.on_svc_message(msg, &msg_buf)?;
-            } else if let Ok(msg) = EBaseUserMessages::try_from(msg_type) {
-                self.on_base_user_message(msg, &msg_buf)?;
-            } else if let Ok(msg) = EBaseGameEvents::try_from(msg_type) {
-                self.on_base_game_event(msg, &msg_buf)?;
-            } else if let Ok(msg) = NetMessages::try_from(msg_type) {
-                self.on_net_message(msg, &msg_buf)?;
+        #[cfg(feature = "deadlock")]
+        if self.context.game != Game::Dota {
+            if let Ok(msg_type) = CitadelUserMessageIds::try_from(msg_type) {
+                return self.on_citadel_user_message(msg_type, msg);
+            } else if let Ok(msg_type) = ECitadelGameEvents::try_from(msg_type) {
+                return self.on_citadel_game_event(msg_type, msg);
             }
         }
 
-        Ok(())
+        if let Ok(msg_type) = SvcMessages::try_from(msg_type) {
+            self.on_svc_message(msg_type, msg)
+        } else if let Ok(msg_type) = EBaseUserMessages::try_from(msg_type) {
+            self.on_base_user_message(msg_type, msg)
+        } else if let Ok(msg_type) = EBaseGameEvents::try_from(msg_type) {
+            self.on_base_game_event(msg_type, msg)
+        } else if let Ok(msg_type) = NetMessages::try_from(msg_type) {
+            self.on_net_message(msg_type, msg)
+        } else {
+            if let Some(stats) = self.stats.as_mut() {
+                stats.add_message(ReplayMessage::Unknown(msg_type), msg.len(), Duration::ZERO);
+            }
+            try_observers!(self, on_unknown_message(&self.context, msg_type, msg))?;
+            Ok(())
+        }
     }
 
     fn dem_full_packet(&mut self, full_packet: CDemoFullPacket) -> Result<(), ParserError> {
-        if self.context.last_full_packet_tick == u32::MAX || self.skip_deltas {
-            self.dem_string_tables(full_packet.string_table.unwrap())?;
-            self.dem_packet(full_packet.packet.unwrap())?;
+        if self.context.last_full_packet_tick == u32::MAX {
+            self.dem_string_tables(
+                full_packet
+                    .string_table
+                    .ok_or(ParserError::IncompleteFullPacket("string tables"))?,
+            )?;
+            self.dem_packet(
+                full_packet
+                    .packet
+                    .ok_or(ParserError::IncompleteFullPacket("packet"))?,
+            )?;
         }
 
         self.context.last_full_packet_tick = self.context.tick;
@@ -187,6 +242,10 @@ impl DemoCommands for Parser<'_> {
 
     fn dem_string_tables(&mut self, msg: CDemoStringTables) -> Result<(), ParserError> {
         for table in msg.tables.iter() {
+            if !self.is_string_table_decoded(table.table_name()) {
+                continue;
+            }
+
             let x = self
                 .context
                 .string_tables
@@ -197,12 +256,14 @@ impl DemoCommands for Parser<'_> {
             for (i, item) in table.items.iter().enumerate() {
                 x.items[i].index = i as i32;
                 x.items[i].key = item.str().to_string();
-                x.items[i].value = Rc::new(item.data().to_vec()).into();
+                x.items[i].value = Arc::new(item.data().to_vec()).into();
                 if table.table_name() == "instancebaseline" {
-                    self.context.baselines.add_baseline(
-                        item.str().parse().unwrap(),
-                        x.items[i].value.as_ref().unwrap().clone(),
-                    );
+                    let id = item.str().parse().map_err(|_| {
+                        StringTableError::InvalidBaselineKey(item.str().to_string())
+                    })?;
+                    self.context
+                        .baselines
+                        .add_baseline(id, x.items[i].value.clone().unwrap_or_default());
                 }
             }
         }
@@ -214,4 +275,10 @@ impl DemoCommands for Parser<'_> {
         self.on_stop()?;
         Ok(())
     }
+
+    fn dem_file_info(&mut self, file_info: CDemoFileInfo) -> Result<(), ParserError> {
+        self.last_tick = file_info.playback_ticks() as u32;
+        self.replay_info = file_info;
+        Ok(())
+    }
 }
@@ -1,4 +1,4 @@
-use crate::error::ParserError;
+use crate::error::{GameEventError, ParserError};
 use crate::parser::demo::svc::SvcMsg;
 use crate::proto::*;
 use crate::Parser;
@@ -71,8 +71,13 @@ impl DemoMessages for Parser<'_> {
         }
 
         if msg_type == EBaseGameEvents::GeSource1LegacyGameEvent {
-            let ge = GameEvent::new(&self.context.game_events, CSvcMsgGameEvent::decode(msg)?);
-            try_observers!(self, on_game_event(&self.context, &ge))?;
+            // Events missing from the list can't be resolved, they are still
+            // passed to on_base_game_event.
+            match GameEvent::new(&self.context.game_events, CSvcMsgGameEvent::decode(msg)?) {
+                Ok(ge) => try_observers!(self, on_game_event(&self.context, &ge))?,
+                Err(GameEventError::UnknownEvent(_)) => {}
+                Err(e) => return Err(e.into()),
+            }
         }
 
         try_observers!(self, on_base_game_event(&self.context, msg_type, msg))?;
@@ -88,7 +93,7 @@ impl DemoMessages for Parser<'_> {
             SvcMessages::SvcUpdateStringTable => {
                 self.update_string_table(CSvcMsgUpdateStringTable::decode(msg)?)?
             }
-            SvcMessages::SvcPacketEntities => {
+            SvcMessages::SvcPacketEntities if !self.skip_entities => {
                 self.packet_entities(CSvcMsgPacketEntities::decode(msg)?)?
             }
             _ => {}
@@ -133,6 +138,7 @@ impl DemoMessages for Parser<'_> {
         }
 
         try_observers!(self, on_tick_end(&self.context))?;
+        self.context.last_tick_end = self.context.tick;
         Ok(())
     }
 
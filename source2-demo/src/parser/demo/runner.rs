@@ -1,20 +1,20 @@
 use crate::error::ParserError;
 use crate::parser::demo::DemoMessages;
-use crate::parser::Parser;
-use crate::proto::*;
-use crate::reader::*;
+use crate::parser::{Keyframe, Parser};
 use std::cmp::min;
 use std::mem;
 
 pub trait DemoRunner {
-    /// Moves to the end of replay. Last packet is [`CDemoFileInfo`].
+    /// Moves to the end of replay. Last packet is
+    /// [`CDemoFileInfo`](crate::proto::CDemoFileInfo).
     fn run_to_end(&mut self) -> Result<(), ParserError>;
 
     /// Moves to target tick.
     fn run_to_tick(&mut self, target_tick: u32) -> Result<(), ParserError>;
 
-    /// Moves to target tick without calling observers and processing delta
-    /// packets.
+    /// Moves to target tick without calling observers. Starts from the
+    /// nearest preceding full packet, so jumping backwards is as cheap as
+    /// jumping forward.
     fn jump_to_tick(&mut self, target_tick: u32) -> Result<(), ParserError>;
 }
 
@@ -22,7 +22,7 @@ impl DemoRunner for Parser<'_> {
     fn run_to_end(&mut self) -> Result<(), ParserError> {
         self.prologue()?;
 
-        while let Some(message) = self.reader.read_next_message()? {
+        while let Some(message) = self.read_next_message()? {
             self.on_tick_start(message.tick)?;
             self.on_demo_command(message.msg_type, message.buf.as_slice())?;
         }
@@ -38,7 +38,7 @@ impl DemoRunner for Parser<'_> {
 
         let target_tick = min(target_tick, self.last_tick);
 
-        while let Some(message) = self.reader.read_next_message()? {
+        while let Some(message) = self.read_next_message()? {
             self.on_tick_start(message.tick)?;
             self.on_demo_command(message.msg_type, message.buf.as_slice())?;
             if self.context.tick >= target_tick {
@@ -51,79 +51,90 @@ impl DemoRunner for Parser<'_> {
     }
 
     fn jump_to_tick(&mut self, target_tick: u32) -> Result<(), ParserError> {
-        let fp_delta = if cfg!(feature = "deadlock") {
-            3600
-        } else {
-            1800
-        };
-
         let target_tick = min(target_tick, self.last_tick);
 
-        if target_tick < self.context.tick {
-            self.context.last_full_packet_tick = u32::MAX;
-            self.context.tick = u32::MAX;
-            self.context.net_tick = u32::MAX;
-            self.reader.reset_to(16);
-
-            self.context.entities.entities_vec = vec![None; 8192];
-
-            self.context.string_tables.tables.clear();
-            self.context.string_tables.name_to_table.clear();
-            self.context.game_events.list.clear();
+        if self.reader.is_seekable() {
+            self.build_keyframe_index()?;
         }
 
-        self.prologue()?;
+        let keyframe = self.keyframes.nearest(target_tick).copied();
+        let current_tick = self.context.tick;
+
+        match keyframe {
+            Some(keyframe)
+                if target_tick < current_tick
+                    || current_tick == u32::MAX
+                    || keyframe.tick > current_tick =>
+            {
+                self.prologue()?;
+                self.seek_to_keyframe(keyframe)?;
+            }
+            None if target_tick < current_tick && current_tick != u32::MAX => {
+                self.reset()?;
+                self.prologue()?;
+            }
+            _ => self.prologue()?,
+        }
 
-        self.skip_deltas = true;
         let observers = mem::take(&mut self.observers);
+        self.skipping = true;
+        let result = self.skip_to_tick(target_tick);
+        self.skipping = false;
+        self.observers = observers;
 
-        let mut first_fp_checked = self.context.last_full_packet_tick != u32::MAX;
-        let mut last_fp_checked = false;
+        result
+    }
+}
 
-        while let Some(mut message) = self.reader.read_next_message()? {
+impl Parser<'_> {
+    fn skip_to_tick(&mut self, target_tick: u32) -> Result<(), ParserError> {
+        while let Some(message) = self.read_next_message()? {
             self.context.previous_tick = self.context.tick;
             self.context.tick = message.tick;
 
-            if message.msg_type == EDemoCommands::DemFullPacket {
-                self.context.last_full_packet_tick = self.context.tick;
+            self.on_demo_command(message.msg_type, message.buf.as_slice())?;
+
+            if self.context.tick >= target_tick {
+                break;
             }
+        }
 
-            let next_fp = self.context.last_full_packet_tick == u32::MAX
-                || self.context.last_full_packet_tick < target_tick
-                    && (target_tick - self.context.last_full_packet_tick) > fp_delta;
+        Ok(())
+    }
 
-            if message.msg_type == EDemoCommands::DemFullPacket {
-                if next_fp && first_fp_checked {
-                    message.msg_type = EDemoCommands::DemStringTables;
-                    message.buf = CDemoFullPacket::decode(message.buf.as_slice())?
-                        .string_table
-                        .unwrap()
-                        .encode_to_vec();
-                }
+    /// Moves reader to given full packet. The packet itself is applied when
+    /// it is read next.
+    fn seek_to_keyframe(&mut self, keyframe: Keyframe) -> Result<(), ParserError> {
+        self.reader.reset_to(keyframe.offset)?;
+        self.corruption = None;
 
-                self.on_demo_command(message.msg_type, message.buf.as_slice())?;
-            }
+        self.context.last_full_packet_tick = u32::MAX;
+        self.context.entities.clear();
 
-            if last_fp_checked {
-                self.on_demo_command(message.msg_type, message.buf.as_slice())?;
-            }
+        #[cfg(feature = "dota")]
+        self.combat_log.clear();
 
-            if message.msg_type == EDemoCommands::DemFullPacket && !first_fp_checked {
-                first_fp_checked = true;
-            }
+        Ok(())
+    }
 
-            if message.msg_type == EDemoCommands::DemFullPacket && !next_fp {
-                last_fp_checked = true;
-                self.skip_deltas = false;
-            }
+    /// Moves reader to the start of replay and drops all state except
+    /// classes and serializers.
+    fn reset(&mut self) -> Result<(), ParserError> {
+        self.reader.reset_to(16)?;
+        self.corruption = None;
 
-            if self.context.tick >= target_tick && first_fp_checked {
-                break;
-            }
-        }
+        self.context.last_full_packet_tick = u32::MAX;
+        self.context.tick = u32::MAX;
+        self.context.net_tick = u32::MAX;
 
-        self.skip_deltas = false;
-        self.observers = observers;
+        self.context.entities.clear();
+
+        self.context.string_tables.tables.clear();
+        self.context.string_tables.name_to_table.clear();
+        self.context.game_events.list.clear();
+
+        #[cfg(feature = "dota")]
+        self.combat_log.clear();
 
         Ok(())
     }
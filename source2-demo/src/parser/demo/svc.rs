@@ -1,10 +1,10 @@
 use crate::entity::field::*;
 use crate::entity::*;
-use crate::error::ParserError;
+use crate::error::{EntityError, ParserError, StringTableError};
 use crate::proto::*;
+use crate::parser::{parse_game_build, Game};
 use crate::reader::*;
 use crate::{try_observers, Parser, StringTable};
-use std::cell::RefCell;
 
 pub trait SvcMsg {
     fn server_info(&mut self, server_info: CSvcMsgServerInfo) -> Result<(), ParserError>;
@@ -30,23 +30,16 @@ impl SvcMsg for Parser<'_> {
         self.context.classes.class_id_size =
             (f64::log2(server_info.max_classes() as f64) + 1.0) as u32;
 
-        let game_dir = server_info.game_dir();
+        if server_info.tick_interval() > 0.0 {
+            self.context.tick_interval = server_info.tick_interval();
+        }
 
-        let game_prefix = if cfg!(feature = "dota") {
-            "dota_v"
-        } else if cfg!(feature = "deadlock") {
-            "citadel_v"
-        } else {
-            "unknown"
-        };
+        if self.context.game == Game::Unknown {
+            self.context.game = Game::from_game_dir(server_info.game_dir());
+        }
 
-        if let Some(start) = game_dir.find(game_prefix) {
-            let start = start + game_prefix.len();
-            if let Some(end) = game_dir[start..].find('/') {
-                let build_str = &game_dir[start..start + end];
-                let build = build_str.parse::<u32>().unwrap();
-                self.context.game_build = build;
-            }
+        if let Some(build) = parse_game_build(server_info.game_dir()) {
+            self.context.game_build = build;
         }
         Ok(())
     }
@@ -63,30 +56,39 @@ impl SvcMsg for Parser<'_> {
             user_data_size: string_table.user_data_size(),
             flags: string_table.flags() as u32,
             var_int_bit_counts: string_table.using_varint_bitcounts(),
-            keys: RefCell::new(vec![String::default(); 32]),
+            keys: vec![String::default(); 32],
         };
 
         let table_index = table.index as usize;
+        let decoded = self.is_string_table_decoded(table.name());
 
-        let buf = if string_table.data_compressed() {
-            let mut decoder = snap::raw::Decoder::new();
-            decoder.decompress_vec(string_table.string_data())?
+        let modified = if decoded {
+            let buf = if string_table.data_compressed() {
+                let mut decoder = snap::raw::Decoder::new();
+                decoder.decompress_vec(string_table.string_data())?
+            } else {
+                string_table.string_data().into()
+            };
+
+            table.parse(
+                &mut self.context.baselines,
+                buf.as_slice(),
+                string_table.num_entries(),
+            )?
         } else {
-            string_table.string_data().into()
+            vec![]
         };
 
-        let modified = table.parse(
-            &mut self.context.baselines,
-            buf.as_slice(),
-            string_table.num_entries(),
-        )?;
-
         self.context
             .string_tables
             .name_to_table
             .insert(table.name().into(), table.index as usize);
         self.context.string_tables.tables.push(table);
 
+        if !decoded {
+            return Ok(());
+        }
+
         try_observers!(
             self,
             on_string_table(
@@ -103,13 +105,24 @@ impl SvcMsg for Parser<'_> {
         &mut self,
         string_table: CSvcMsgUpdateStringTable,
     ) -> Result<(), ParserError> {
+        let table_name = self
+            .context
+            .string_tables
+            .tables
+            .get(string_table.table_id() as usize)
+            .map(|table| table.name())
+            .unwrap_or_default();
+        if !self.is_string_table_decoded(table_name) {
+            return Ok(());
+        }
+
         let modified = {
             let table = self
                 .context
                 .string_tables
                 .tables
                 .get_mut(string_table.table_id() as usize)
-                .unwrap();
+                .ok_or(StringTableError::TableNotFoundById(string_table.table_id()))?;
 
             table.parse(
                 &mut self.context.baselines,
@@ -146,80 +159,94 @@ impl SvcMsg for Parser<'_> {
                 continue;
             }
 
-            match EntityEvents::from_cmd(cmd) {
+            if index >= self.context.entities.entities_vec.len() {
+                return Err(EntityError::IndexNotFound(index).into());
+            }
+
+            match EntityEvents::from_cmd(cmd)? {
                 EntityEvents::Created => {
                     let class_id = reader.read_bits(self.context.classes.class_id_size) as i32;
                     let serial = reader.read_bits(17);
                     let _ = reader.read_var_u32();
 
-                    let class = self.context.classes.get_by_id_rc(class_id as usize).clone();
-
-                    let entity_baseline = self
-                        .context
-                        .baselines
-                        .states
-                        .entry(class_id)
-                        .or_insert_with(|| {
-                            let mut state = FieldState::default();
-                            self.field_reader.read_fields(
-                                &mut Reader::new(&self.context.baselines.baselines[&class_id]),
+                    let class = self.context.classes.get_by_id_arc(class_id as usize)?.clone();
+
+                    if !self.context.baselines.states.contains_key(&class_id) {
+                        let baseline = self
+                            .context
+                            .baselines
+                            .baselines
+                            .get(&class_id)
+                            .ok_or(EntityError::BaselineNotFound(class_id))?;
+                        let mut state = FieldState::default();
+                        self.field_reader
+                            .read_fields(
+                                &mut Reader::new(baseline),
                                 &class.serializer,
                                 &mut state,
-                            );
-                            state
-                        })
-                        .clone();
-
-                    self.context.entities.entities_vec[index] = Some(Entity::new(
-                        index as u32,
-                        serial,
-                        class.clone(),
-                        entity_baseline,
-                    ));
-
-                    let entity = self.context.entities.entities_vec[index].as_mut().unwrap();
-
-                    self.field_reader.read_fields(
-                        &mut reader,
-                        &entity.class.serializer,
-                        &mut entity.state,
+                                PropertyTracking::Disabled,
+                                &mut Vec::new(),
+                            )
+                            .map_err(|e| EntityError::CorruptedData(index, e))?;
+                        self.context.baselines.states.insert(class_id, state);
+                    }
+                    let entity_baseline = self.context.baselines.states[&class_id].clone();
+
+                    let entity = self.context.entities.insert(
+                        index,
+                        Entity::new(index as u32, serial, class, entity_baseline),
                     );
 
-                    try_observers!(
-                        self,
-                        on_entity(
-                            &self.context,
-                            EntityEvents::Created,
-                            self.context.entities.entities_vec[index].as_ref().unwrap()
+                    self.context.property_changes.clear();
+                    let fields = self
+                        .field_reader
+                        .read_fields(
+                            &mut reader,
+                            &entity.class.serializer,
+                            &mut entity.state,
+                            self.property_tracking,
+                            &mut self.context.property_changes,
                         )
-                    )?;
+                        .map_err(|e| EntityError::CorruptedData(index, e))?;
+                    if let Some(stats) = self.stats.as_mut() {
+                        stats.add_entity(entity.class.name(), EntityEvents::Created, fields);
+                    }
+
+                    self.notify_entity(EntityEvents::Created, index)?;
                 }
                 EntityEvents::Updated => {
-                    let entity = self.context.entities.entities_vec[index].as_mut().unwrap();
-
-                    self.field_reader.read_fields(
-                        &mut reader,
-                        &entity.class.serializer,
-                        &mut entity.state,
-                    );
-
-                    try_observers!(
-                        self,
-                        on_entity(
-                            &self.context,
-                            EntityEvents::Updated,
-                            self.context.entities.entities_vec[index].as_ref().unwrap()
+                    let entity = self.context.entities.entities_vec[index]
+                        .as_mut()
+                        .ok_or(EntityError::IndexNotFound(index))?;
+
+                    self.context.property_changes.clear();
+                    let fields = self
+                        .field_reader
+                        .read_fields(
+                            &mut reader,
+                            &entity.class.serializer,
+                            &mut entity.state,
+                            self.property_tracking,
+                            &mut self.context.property_changes,
                         )
-                    )?;
+                        .map_err(|e| EntityError::CorruptedData(index, e))?;
+                    if let Some(stats) = self.stats.as_mut() {
+                        stats.add_entity(entity.class.name(), EntityEvents::Updated, fields);
+                    }
+
+                    self.notify_entity(EntityEvents::Updated, index)?;
                 }
                 EntityEvents::Deleted => {
                     if let Some(entity) = self.context.entities.entities_vec[index].as_ref() {
+                        if let Some(stats) = self.stats.as_mut() {
+                            stats.add_entity(entity.class.name(), EntityEvents::Deleted, 0);
+                        }
                         try_observers!(
                             self,
                             on_entity(&self.context, EntityEvents::Deleted, entity)
                         )?;
                     }
-                    self.context.entities.entities_vec[index] = None;
+                    self.context.entities.remove(index);
                 }
             }
         }
@@ -227,3 +254,20 @@ impl SvcMsg for Parser<'_> {
         Ok(())
     }
 }
+
+impl Parser<'_> {
+    /// Calls [`crate::Observer::on_entity`] and then
+    /// [`crate::Observer::on_property_change`] for every recorded change.
+    fn notify_entity(&mut self, event: EntityEvents, index: usize) -> Result<(), ParserError> {
+        let entity = self.context.entities.entities_vec[index].as_ref().unwrap();
+
+        try_observers!(self, on_entity(&self.context, event, entity))?;
+
+        for change in self.context.property_changes.iter() {
+            try_observers!(self, on_property_change(&self.context, entity, change))?;
+        }
+
+        self.context.property_changes.clear();
+        Ok(())
+    }
+}
@@ -0,0 +1,327 @@
+use crate::error::ParserError;
+use crate::parser::demo::DemoMessages;
+use crate::parser::{Context, Observer, ObserverHandle, ObserverResult, Parser};
+use crate::proto::*;
+use crate::{Entity, EntityEvents, StringTable};
+use std::collections::VecDeque;
+
+#[cfg(feature = "dota")]
+use crate::event::CombatLogEntry;
+
+/// Event yielded by [`Events`] iterator.
+#[derive(Debug, Clone)]
+pub enum ReplayEvent {
+    TickStart(u32),
+    TickEnd(u32),
+    /// Entity event and entity index. Deleted entities are already removed
+    /// from [`Context`] when the event is yielded.
+    Entity(EntityEvents, u32),
+    /// Raw game event, use [`GameEvent::new`](crate::GameEvent::new) with
+    /// [`Context::game_events`] to resolve names and keys.
+    GameEvent(CSvcMsgGameEvent),
+    #[cfg(feature = "dota")]
+    CombatLog(Box<CMsgDotaCombatLogEntry>),
+    /// String table name and indices of modified rows.
+    StringTable(String, Vec<i32>),
+    /// Protobuf message, only yielded if enabled with
+    /// [`Events::with_messages`].
+    Message(ReplayMessage, Vec<u8>),
+    Stop,
+}
+
+/// Type of message in [`ReplayEvent::Message`].
+#[derive(Debug, Clone, Copy, Eq, PartialEq, Hash)]
+pub enum ReplayMessage {
+    Demo(EDemoCommands),
+    Net(NetMessages),
+    Svc(SvcMessages),
+    BaseUser(EBaseUserMessages),
+    BaseGameEvent(EBaseGameEvents),
+    #[cfg(feature = "dota")]
+    DotaUser(EDotaUserMessages),
+    #[cfg(feature = "deadlock")]
+    CitadelUser(CitadelUserMessageIds),
+    #[cfg(feature = "deadlock")]
+    CitadelGameEvent(ECitadelGameEvents),
+    /// Packet message with unknown id, see [`Observer::on_unknown_message`].
+    Unknown(i32),
+}
+
+/// Pull-based alternative to [`Observer`] callbacks, created with
+/// [`Parser::events`]. Registered observers keep receiving callbacks while
+/// iterating.
+///
+/// Events are collected while the whole outer demo message is parsed, so
+/// [`Events::context`] runs ahead of the yielded events: it returns replay
+/// state after the last parsed message, which may already include later
+/// entity updates, string table changes or even the next tick. Use it for
+/// data which doesn't change during the replay (classes, game event list)
+/// and register an [`Observer`] if exact per-event state is needed.
+///
+/// # Examples
+///
+/// ```no_run
+/// use source2_demo::prelude::*;
+///
+/// # fn main() -> Result<(), Box<dyn std::error::Error>> {
+/// let replay = std::fs::read("replay.dem")?;
+/// let mut parser = Parser::new(&replay)?;
+///
+/// let mut events = parser.events();
+/// let mut tick = 0;
+/// while let Some(event) = events.next() {
+///     match event? {
+///         ReplayEvent::TickStart(t) => tick = t,
+///         ReplayEvent::GameEvent(ge) => {
+///             let ge = GameEvent::new(events.context().game_events(), ge)?;
+///             println!("{}: {}", tick, ge.name());
+///         }
+///         _ => {}
+///     }
+/// }
+/// # Ok(())
+/// # }
+/// ```
+///
+/// ```no_run
+/// use source2_demo::prelude::*;
+///
+/// # fn main() -> Result<(), Box<dyn std::error::Error>> {
+/// let replay = std::fs::read("replay.dem")?;
+/// let mut parser = Parser::new(&replay)?;
+///
+/// let mut deaths = 0;
+/// for event in parser.events() {
+///     if let ReplayEvent::Entity(EntityEvents::Deleted, _) = event? {
+///         deaths += 1;
+///     }
+/// }
+/// # Ok(())
+/// # }
+/// ```
+pub struct Events<'p, 'a> {
+    parser: &'p mut Parser<'a>,
+    collector: ObserverHandle<EventCollector>,
+    started: bool,
+    finished: bool,
+}
+
+impl<'a> Parser<'a> {
+    /// Returns iterator over replay events, see [`Events`].
+    pub fn events(&mut self) -> Events<'_, 'a> {
+        let collector = self.add_observer(EventCollector::default());
+
+        Events {
+            parser: self,
+            collector,
+            started: false,
+            finished: false,
+        }
+    }
+}
+
+impl Events<'_, '_> {
+    /// Enables [`ReplayEvent::Message`] events. Every message is copied, so
+    /// it's disabled by default.
+    pub fn with_messages(self) -> Self {
+        self.collector.borrow_mut().messages = true;
+        self
+    }
+
+    /// Returns replay state after the last parsed message, see [`Events`].
+    pub fn context(&self) -> &Context {
+        &self.parser.context
+    }
+
+    fn advance(&mut self) -> Result<(), ParserError> {
+        if !self.started {
+            self.started = true;
+            return self.parser.prologue();
+        }
+
+        if let Some(message) = self.parser.read_next_message()? {
+            self.parser.on_tick_start(message.tick)?;
+            self.parser
+                .on_demo_command(message.msg_type, message.buf.as_slice())?;
+        } else {
+            self.finished = true;
+            self.parser.on_tick_end()?;
+        }
+
+        Ok(())
+    }
+}
+
+impl Iterator for Events<'_, '_> {
+    type Item = Result<ReplayEvent, ParserError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            if let Some(event) = self.collector.borrow_mut().queue.pop_front() {
+                return Some(Ok(event));
+            }
+
+            if self.finished {
+                return None;
+            }
+
+            if let Err(e) = self.advance() {
+                self.finished = true;
+                return Some(Err(e));
+            }
+        }
+    }
+}
+
+impl Drop for Events<'_, '_> {
+    fn drop(&mut self) {
+        self.parser.unregister_observer(&self.collector);
+    }
+}
+
+#[derive(Default)]
+struct EventCollector {
+    queue: VecDeque<ReplayEvent>,
+    messages: bool,
+}
+
+impl EventCollector {
+    fn message(&mut self, msg_type: ReplayMessage, msg: &[u8]) -> ObserverResult {
+        if self.messages {
+            self.queue
+                .push_back(ReplayEvent::Message(msg_type, msg.to_vec()));
+        }
+        Ok(())
+    }
+}
+
+impl Observer for EventCollector {
+    fn on_demo_command(
+        &mut self,
+        _ctx: &Context,
+        msg_type: EDemoCommands,
+        msg: &[u8],
+    ) -> ObserverResult {
+        self.message(ReplayMessage::Demo(msg_type), msg)
+    }
+
+    fn on_net_message(
+        &mut self,
+        _ctx: &Context,
+        msg_type: NetMessages,
+        msg: &[u8],
+    ) -> ObserverResult {
+        self.message(ReplayMessage::Net(msg_type), msg)
+    }
+
+    fn on_svc_message(
+        &mut self,
+        _ctx: &Context,
+        msg_type: SvcMessages,
+        msg: &[u8],
+    ) -> ObserverResult {
+        self.message(ReplayMessage::Svc(msg_type), msg)
+    }
+
+    fn on_base_user_message(
+        &mut self,
+        _ctx: &Context,
+        msg_type: EBaseUserMessages,
+        msg: &[u8],
+    ) -> ObserverResult {
+        self.message(ReplayMessage::BaseUser(msg_type), msg)
+    }
+
+    fn on_base_game_event(
+        &mut self,
+        _ctx: &Context,
+        msg_type: EBaseGameEvents,
+        msg: &[u8],
+    ) -> ObserverResult {
+        if msg_type == EBaseGameEvents::GeSource1LegacyGameEvent {
+            self.queue
+                .push_back(ReplayEvent::GameEvent(CSvcMsgGameEvent::decode(msg)?));
+        }
+        self.message(ReplayMessage::BaseGameEvent(msg_type), msg)
+    }
+
+    fn on_unknown_message(&mut self, _ctx: &Context, msg_type: i32, msg: &[u8]) -> ObserverResult {
+        self.message(ReplayMessage::Unknown(msg_type), msg)
+    }
+
+    fn on_tick_start(&mut self, ctx: &Context) -> ObserverResult {
+        self.queue.push_back(ReplayEvent::TickStart(ctx.tick()));
+        Ok(())
+    }
+
+    fn on_tick_end(&mut self, ctx: &Context) -> ObserverResult {
+        self.queue.push_back(ReplayEvent::TickEnd(ctx.tick()));
+        Ok(())
+    }
+
+    fn on_entity(
+        &mut self,
+        _ctx: &Context,
+        event: EntityEvents,
+        entity: &Entity,
+    ) -> ObserverResult {
+        self.queue
+            .push_back(ReplayEvent::Entity(event, entity.index()));
+        Ok(())
+    }
+
+    fn on_string_table(
+        &mut self,
+        _ctx: &Context,
+        st: &StringTable,
+        modified: &[i32],
+    ) -> ObserverResult {
+        self.queue.push_back(ReplayEvent::StringTable(
+            st.name().to_string(),
+            modified.to_vec(),
+        ));
+        Ok(())
+    }
+
+    fn on_stop(&mut self, _ctx: &Context) -> ObserverResult {
+        self.queue.push_back(ReplayEvent::Stop);
+        Ok(())
+    }
+
+    #[cfg(feature = "dota")]
+    fn on_combat_log(&mut self, _ctx: &Context, cle: &CombatLogEntry) -> ObserverResult {
+        self.queue
+            .push_back(ReplayEvent::CombatLog(Box::new(cle.log().clone())));
+        Ok(())
+    }
+
+    #[cfg(feature = "dota")]
+    fn on_dota_user_message(
+        &mut self,
+        _ctx: &Context,
+        msg_type: EDotaUserMessages,
+        msg: &[u8],
+    ) -> ObserverResult {
+        self.message(ReplayMessage::DotaUser(msg_type), msg)
+    }
+
+    #[cfg(feature = "deadlock")]
+    fn on_citadel_game_event(
+        &mut self,
+        _ctx: &Context,
+        msg_type: ECitadelGameEvents,
+        msg: &[u8],
+    ) -> ObserverResult {
+        self.message(ReplayMessage::CitadelGameEvent(msg_type), msg)
+    }
+
+    #[cfg(feature = "deadlock")]
+    fn on_citadel_user_message(
+        &mut self,
+        _ctx: &Context,
+        msg_type: CitadelUserMessageIds,
+        msg: &[u8],
+    ) -> ObserverResult {
+        self.message(ReplayMessage::CitadelUser(msg_type), msg)
+    }
+}
@@ -0,0 +1,34 @@
+/// Game the replay was recorded in. Detected at runtime from game directory
+/// in [`CDemoFileHeader`](crate::proto::CDemoFileHeader) or
+/// [`CSvcMsgServerInfo`](crate::proto::CSvcMsgServerInfo), so one binary
+/// built with both `dota` and `deadlock` features handles replays of both
+/// games.
+#[derive(Debug, Clone, Copy, Eq, PartialEq, Hash, Default)]
+pub enum Game {
+    #[default]
+    Unknown,
+    Dota,
+    Deadlock,
+}
+
+impl Game {
+    pub(crate) fn from_game_dir(game_dir: &str) -> Self {
+        if game_dir.contains("citadel") {
+            Game::Deadlock
+        } else if game_dir.contains("dota") {
+            Game::Dota
+        } else {
+            Game::Unknown
+        }
+    }
+}
+
+/// Extracts build number from game directory, e.g.
+/// `/opt/srcds/dota/dota_v6543/dota`.
+pub(crate) fn parse_game_build(game_dir: &str) -> Option<u32> {
+    ["dota_v", "citadel_v"].iter().find_map(|prefix| {
+        let start = game_dir.find(prefix)? + prefix.len();
+        let end = game_dir[start..].find('/')?;
+        game_dir[start..start + end].parse().ok()
+    })
+}
@@ -0,0 +1,150 @@
+use crate::error::KeyframeIndexError;
+use std::path::{Path, PathBuf};
+
+const MAGIC: &[u8; 8] = b"S2DEMIDX";
+const VERSION: u32 = 1;
+
+/// Position of a [`CDemoFullPacket`](crate::proto::CDemoFullPacket) in
+/// replay.
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+pub struct Keyframe {
+    pub(crate) tick: u32,
+    pub(crate) offset: usize,
+}
+
+impl Keyframe {
+    pub fn tick(&self) -> u32 {
+        self.tick
+    }
+
+    /// Byte offset of the outer message from the start of replay.
+    pub fn offset(&self) -> usize {
+        self.offset
+    }
+}
+
+/// Index of all full packets in replay, sorted by offset. Full packets
+/// contain complete string tables and entity state, so parsing can start
+/// from any of them.
+#[derive(Debug, Clone, Default)]
+pub struct KeyframeIndex {
+    pub(crate) keyframes: Vec<Keyframe>,
+    pub(crate) complete: bool,
+    /// Replay was scanned, up to the last complete message if truncated.
+    pub(crate) scanned: bool,
+}
+
+impl KeyframeIndex {
+    /// Iterator over all keyframes.
+    pub fn iter(&self) -> impl Iterator<Item = &Keyframe> {
+        self.keyframes.iter()
+    }
+
+    pub fn len(&self) -> usize {
+        self.keyframes.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.keyframes.is_empty()
+    }
+
+    /// Returns `true` if the index was built by scanning the whole replay.
+    /// Otherwise it contains only full packets the parser has gone through
+    /// or the replay is truncated.
+    pub fn is_complete(&self) -> bool {
+        self.complete
+    }
+
+    /// Returns the last [`Keyframe`] at or before given tick.
+    pub fn nearest(&self, tick: u32) -> Option<&Keyframe> {
+        let idx = self.keyframes.partition_point(|kf| kf.tick <= tick);
+        idx.checked_sub(1).map(|i| &self.keyframes[i])
+    }
+
+    /// Returns sidecar file path for given replay, e.g. `match.dem.idx` for
+    /// `match.dem`.
+    pub fn sidecar_path(replay_path: impl AsRef<Path>) -> PathBuf {
+        let mut path = replay_path.as_ref().as_os_str().to_owned();
+        path.push(".idx");
+        path.into()
+    }
+
+    /// Serializes complete index together with replay length and playback
+    /// ticks, which are checked on load.
+    pub(crate) fn encode(&self, replay_len: usize, playback_ticks: u32) -> Vec<u8> {
+        let mut buf = Vec::with_capacity(28 + self.keyframes.len() * 12);
+        buf.extend_from_slice(MAGIC);
+        buf.extend_from_slice(&VERSION.to_le_bytes());
+        buf.extend_from_slice(&(replay_len as u64).to_le_bytes());
+        buf.extend_from_slice(&playback_ticks.to_le_bytes());
+        buf.extend_from_slice(&(self.keyframes.len() as u32).to_le_bytes());
+        for keyframe in self.keyframes.iter() {
+            buf.extend_from_slice(&keyframe.tick.to_le_bytes());
+            buf.extend_from_slice(&(keyframe.offset as u64).to_le_bytes());
+        }
+        buf
+    }
+
+    pub(crate) fn decode(
+        buf: &[u8],
+        replay_len: usize,
+        playback_ticks: u32,
+    ) -> Result<Self, KeyframeIndexError> {
+        let mut pos = 0;
+        let mut take = |n: usize| -> Result<&[u8], KeyframeIndexError> {
+            let bytes = buf.get(pos..pos + n).ok_or(KeyframeIndexError::Truncated)?;
+            pos += n;
+            Ok(bytes)
+        };
+        let u32 = |b: &[u8]| u32::from_le_bytes(b.try_into().unwrap());
+        let u64 = |b: &[u8]| u64::from_le_bytes(b.try_into().unwrap());
+
+        if take(8)? != MAGIC {
+            return Err(KeyframeIndexError::WrongMagic);
+        }
+
+        let version = u32(take(4)?);
+        if version != VERSION {
+            return Err(KeyframeIndexError::UnsupportedVersion(version));
+        }
+
+        if u64(take(8)?) != replay_len as u64 || u32(take(4)?) != playback_ticks {
+            return Err(KeyframeIndexError::Stale);
+        }
+
+        let count = u32(take(4)?) as usize;
+        let mut index = KeyframeIndex {
+            keyframes: Vec::with_capacity(count.min(buf.len() / 12)),
+            complete: true,
+            scanned: true,
+        };
+        for _ in 0..count {
+            let tick = u32(take(4)?);
+            let offset = u64(take(8)?) as usize;
+            if offset < 16
+                || offset >= replay_len
+                || index.keyframes.last().is_some_and(|kf| kf.offset >= offset)
+            {
+                return Err(KeyframeIndexError::Stale);
+            }
+            index.keyframes.push(Keyframe { tick, offset });
+        }
+
+        Ok(index)
+    }
+
+    pub(crate) fn insert(&mut self, keyframe: Keyframe) {
+        if self
+            .keyframes
+            .last()
+            .map_or(true, |last| last.offset < keyframe.offset)
+        {
+            self.keyframes.push(keyframe);
+        } else if let Err(i) = self
+            .keyframes
+            .binary_search_by_key(&keyframe.offset, |kf| kf.offset)
+        {
+            self.keyframes.insert(i, keyframe);
+        }
+    }
+}
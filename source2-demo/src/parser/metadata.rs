@@ -0,0 +1,270 @@
+use crate::error::ParserError;
+use crate::parser::{parse_game_build, Game, Parser};
+use crate::proto::c_game_info::c_dota_game_info::{CHeroSelectEvent, CPlayerInfo};
+use crate::proto::*;
+use crate::reader::*;
+use std::io::Read;
+
+/// Player listed in replay footer.
+#[derive(Debug, Clone, Default)]
+pub struct PlayerMetadata {
+    pub(crate) name: String,
+    pub(crate) hero: String,
+    pub(crate) steam_id: u64,
+    pub(crate) team: i32,
+    pub(crate) is_bot: bool,
+}
+
+impl PlayerMetadata {
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// Hero entity name, e.g. `npc_dota_hero_axe`.
+    pub fn hero(&self) -> &str {
+        &self.hero
+    }
+
+    pub fn steam_id(&self) -> u64 {
+        self.steam_id
+    }
+
+    /// Team number, `2` for Radiant and `3` for Dire.
+    pub fn team(&self) -> i32 {
+        self.team
+    }
+
+    pub fn is_bot(&self) -> bool {
+        self.is_bot
+    }
+}
+
+impl From<&CPlayerInfo> for PlayerMetadata {
+    fn from(info: &CPlayerInfo) -> Self {
+        PlayerMetadata {
+            name: info.player_name().to_string(),
+            hero: info.hero_name().to_string(),
+            steam_id: info.steamid(),
+            team: info.game_team(),
+            is_bot: info.is_fake_client(),
+        }
+    }
+}
+
+/// Pick or ban in the draft.
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+pub struct DraftEvent {
+    pub(crate) is_pick: bool,
+    pub(crate) team: u32,
+    pub(crate) hero_id: i32,
+}
+
+impl DraftEvent {
+    pub fn is_pick(&self) -> bool {
+        self.is_pick
+    }
+
+    /// Team number, `2` for Radiant and `3` for Dire.
+    pub fn team(&self) -> u32 {
+        self.team
+    }
+
+    pub fn hero_id(&self) -> i32 {
+        self.hero_id
+    }
+}
+
+impl From<&CHeroSelectEvent> for DraftEvent {
+    fn from(event: &CHeroSelectEvent) -> Self {
+        DraftEvent {
+            is_pick: event.is_pick(),
+            team: event.team(),
+            hero_id: event.hero_id(),
+        }
+    }
+}
+
+/// Match information which doesn't require parsing of replay body. Game
+/// fields come from [`CDemoFileInfo`] at the end of replay, which carries
+/// game info only for Dota 2, Deadlock replays have only duration there
+/// (see `Parser::deadlock_match_details`). Build and map come from
+/// [`CDemoFileHeader`] at the start.
+///
+/// # Examples
+///
+/// ```no_run
+/// use source2_demo::prelude::*;
+///
+/// # fn main() -> Result<(), Box<dyn std::error::Error>> {
+/// let replay = std::fs::read("replay.dem")?;
+/// let metadata = ReplayMetadata::from_replay(&replay)?;
+///
+/// println!("{:?} {:?}ms", metadata.match_id(), metadata.duration_ms());
+/// for player in metadata.players() {
+///     println!("{}: {}", player.name(), player.hero());
+/// }
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct ReplayMetadata {
+    pub(crate) header: Option<CDemoFileHeader>,
+    pub(crate) duration_ms: Option<u64>,
+    pub(crate) playback_ticks: Option<u32>,
+    pub(crate) match_id: Option<u64>,
+    pub(crate) game_mode: Option<i32>,
+    pub(crate) winner: Option<i32>,
+    pub(crate) league_id: Option<u32>,
+    pub(crate) end_time: Option<u32>,
+    pub(crate) players: Vec<PlayerMetadata>,
+    pub(crate) draft: Vec<DraftEvent>,
+}
+
+impl ReplayMetadata {
+    /// Reads file header and file info. Unlike [`Parser::new`] it doesn't
+    /// fail if file info is missing (e.g. replay is truncated), game fields
+    /// are `None` in that case.
+    pub fn from_replay(replay: &[u8]) -> Result<Self, ParserError> {
+        let mut reader = Reader::new(replay);
+
+        if replay.len() < 16 || reader.read_bytes(8) != b"PBDEMS2\0" {
+            return Err(ParserError::WrongMagic);
+        }
+        reader.read_bytes(8);
+
+        let header = read_file_header(reader.read_next_message()?)?;
+
+        let mut metadata = reader
+            .read_replay_info()
+            .map(|info| ReplayMetadata::from(&info))
+            .unwrap_or_default();
+        metadata.header = Some(header);
+
+        Ok(metadata)
+    }
+
+    /// Reads only file header from any [`Read`] source, game fields are
+    /// `None`. Only the first outer message is read.
+    pub fn from_header<R: Read>(reader: R) -> Result<Self, ParserError> {
+        let mut reader = StreamReader::new(reader);
+
+        if &reader.read_header()?[..8] != b"PBDEMS2\0" {
+            return Err(ParserError::WrongMagic);
+        }
+
+        Ok(ReplayMetadata {
+            header: Some(read_file_header(reader.read_next_message()?)?),
+            ..Default::default()
+        })
+    }
+
+    pub fn header(&self) -> Option<&CDemoFileHeader> {
+        self.header.as_ref()
+    }
+
+    /// Game detected from file header.
+    pub fn game(&self) -> Game {
+        self.header
+            .as_ref()
+            .map_or(Game::Unknown, |header| Game::from_game_dir(header.game_directory()))
+    }
+
+    /// Game build parsed from game directory in file header.
+    pub fn game_build(&self) -> Option<u32> {
+        self.header
+            .as_ref()
+            .and_then(|header| parse_game_build(header.game_directory()))
+    }
+
+    pub fn map_name(&self) -> Option<&str> {
+        self.header.as_ref().map(|header| header.map_name())
+    }
+
+    pub fn duration_ms(&self) -> Option<u64> {
+        self.duration_ms
+    }
+
+    pub fn playback_ticks(&self) -> Option<u32> {
+        self.playback_ticks
+    }
+
+    pub fn match_id(&self) -> Option<u64> {
+        self.match_id
+    }
+
+    /// Raw game mode, see [`ReplayMetadata::dota_game_mode`].
+    pub fn game_mode(&self) -> Option<i32> {
+        self.game_mode
+    }
+
+    #[cfg(feature = "dota")]
+    pub fn dota_game_mode(&self) -> Option<DotaGameMode> {
+        self.game_mode
+            .and_then(|mode| DotaGameMode::try_from(mode).ok())
+    }
+
+    /// Winning team number, `2` for Radiant and `3` for Dire.
+    pub fn winner(&self) -> Option<i32> {
+        self.winner
+    }
+
+    pub fn league_id(&self) -> Option<u32> {
+        self.league_id
+    }
+
+    /// Unix timestamp of the match end.
+    pub fn end_time(&self) -> Option<u32> {
+        self.end_time
+    }
+
+    pub fn players(&self) -> &[PlayerMetadata] {
+        &self.players
+    }
+
+    /// Picks and bans in draft order.
+    pub fn draft(&self) -> &[DraftEvent] {
+        &self.draft
+    }
+}
+
+impl From<&CDemoFileInfo> for ReplayMetadata {
+    fn from(info: &CDemoFileInfo) -> Self {
+        let dota = info.game_info.as_ref().and_then(|x| x.dota.as_ref());
+
+        ReplayMetadata {
+            header: None,
+            duration_ms: info
+                .playback_time
+                .map(|time| (time.max(0.0) * 1000.0).round() as u64),
+            playback_ticks: info.playback_ticks.map(|ticks| ticks as u32),
+            match_id: dota.and_then(|x| x.match_id),
+            game_mode: dota.and_then(|x| x.game_mode),
+            winner: dota.and_then(|x| x.game_winner),
+            league_id: dota.and_then(|x| x.leagueid),
+            end_time: dota.and_then(|x| x.end_time),
+            players: dota.map_or_else(Vec::new, |x| {
+                x.player_info.iter().map(PlayerMetadata::from).collect()
+            }),
+            draft: dota.map_or_else(Vec::new, |x| {
+                x.picks_bans.iter().map(DraftEvent::from).collect()
+            }),
+        }
+    }
+}
+
+impl Parser<'_> {
+    /// Returns [`ReplayMetadata`] from [`Parser::replay_info`]. Header
+    /// fields are `None`, use [`ReplayMetadata::from_replay`] for them.
+    pub fn metadata(&self) -> ReplayMetadata {
+        ReplayMetadata::from(&self.replay_info)
+    }
+}
+
+fn read_file_header(message: Option<OuterMessage>) -> Result<CDemoFileHeader, ParserError> {
+    match message {
+        Some(message) if message.msg_type == EDemoCommands::DemFileHeader => {
+            Ok(CDemoFileHeader::decode(message.buf.as_slice())?)
+        }
+        _ => Err(ParserError::ReplayEncodingError),
+    }
+}
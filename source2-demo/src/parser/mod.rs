@@ -1,40 +1,88 @@
+mod anonymizer;
+mod checkpoint;
+mod codegen;
 mod context;
 mod demo;
+mod events;
+mod game;
+mod keyframes;
+mod metadata;
 mod observer;
+mod parallel;
+mod progress;
+mod stats;
+mod writer;
 
+pub use anonymizer::*;
+pub use codegen::*;
 pub use context::*;
 pub use demo::runner::*;
+pub use events::*;
+pub use game::*;
+pub use keyframes::*;
+pub use metadata::*;
 pub use observer::*;
+pub use parallel::*;
+pub use progress::*;
+pub use stats::*;
+pub use writer::*;
 
 use crate::error::*;
 use crate::proto::*;
 use crate::reader::*;
-use std::cell::RefCell;
-use std::rc::Rc;
+use hashbrown::HashSet;
+use std::io::Read;
+use std::path::Path;
+use std::time::Instant;
 
-use crate::parser::demo::DemoCommands;
+use crate::parser::demo::{DemoCommands, DemoMessages};
 use crate::try_observers;
+use crate::PropertyTracking;
 #[cfg(feature = "dota")]
 use std::collections::VecDeque;
 
 pub struct Parser<'a> {
-    pub(crate) reader: Reader<'a>,
+    pub(crate) reader: DemoReader<'a>,
     pub(crate) field_reader: FieldReader,
-    pub(crate) observers: Vec<Rc<RefCell<dyn Observer + 'a>>>,
+    pub(crate) observers: Vec<ObserverEntry<'a>>,
+    pub(crate) next_observer_id: usize,
 
     #[cfg(feature = "dota")]
     pub(crate) combat_log: VecDeque<CMsgDotaCombatLogEntry>,
 
     pub(crate) prologue_completed: bool,
-    pub(crate) skip_deltas: bool,
+    pub(crate) keyframes: KeyframeIndex,
+
+    pub(crate) skip_entities: bool,
+    pub(crate) property_tracking: PropertyTracking,
+    pub(crate) string_tables_filter: Option<HashSet<Box<str>>>,
+
+    pub(crate) lenient: bool,
+    pub(crate) corruption: Option<ParserError>,
+    pub(crate) message_offset: usize,
+    /// Set while observers are detached for skipping to a tick.
+    pub(crate) skipping: bool,
+
+    pub(crate) progress_callback: Option<ProgressCallback<'a>>,
+    pub(crate) cancellation: CancellationToken,
+    pub(crate) stats: Option<Box<ParserStats>>,
 
     pub(crate) replay_info: CDemoFileInfo,
     pub(crate) last_tick: u32,
     pub(crate) context: Context,
 }
 
+#[cfg(feature = "send")]
+const _: () = {
+    const fn assert_send<T: Send>() {}
+    assert_send::<Parser>();
+};
+
 impl<'a> Parser<'a> {
     /// Creates new instance of parser and performs validation of replay file.
+    /// If [`CDemoFileInfo`] at the end of replay is missing (e.g. replay is
+    /// truncated), [`Parser::replay_info`] stays empty as with
+    /// [`Parser::from_reader`].
     pub fn new(replay: &'a [u8]) -> Result<Self, ParserError> {
         let mut reader = Reader::new(replay);
 
@@ -44,25 +92,77 @@ impl<'a> Parser<'a> {
 
         reader.read_bytes(8);
 
-        let replay_info = reader.read_replay_info()?;
-        let last_tick = replay_info.playback_ticks() as u32;
+        let replay_info = reader.read_replay_info().unwrap_or_default();
+
+        Ok(Parser::with_reader(DemoReader::Slice(Box::new(reader)), replay_info))
+    }
+
+    /// Creates new instance of parser that pulls messages from any [`Read`]
+    /// source (pipe, archive member, socket) and keeps only the current
+    /// message in memory.
+    ///
+    /// [`CDemoFileInfo`] is stored at the end of replay, so
+    /// [`Parser::replay_info`] stays empty until it is reached. Jumping
+    /// backwards is not supported.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// use source2_demo::prelude::*;
+    ///
+    /// # fn main() -> Result<(), Box<dyn std::error::Error>> {
+    /// let mut parser = Parser::from_reader(std::io::stdin())?;
+    /// parser.run_to_end()?;
+    /// println!("{}", parser.replay_info().playback_ticks());
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn from_reader<R: Read + MaybeSend + 'a>(reader: R) -> Result<Self, ParserError> {
+        let mut reader = StreamReader::new(Box::new(reader) as Box<DynRead<'a>>);
+
+        if &reader.read_header()?[..8] != b"PBDEMS2\0" {
+            return Err(ParserError::WrongMagic);
+        }
+
+        Ok(Parser::with_reader(
+            DemoReader::Stream(reader),
+            CDemoFileInfo::default(),
+        ))
+    }
+
+    fn with_reader(reader: DemoReader<'a>, replay_info: CDemoFileInfo) -> Self {
+        let last_tick = replay_info.playback_ticks.map_or(u32::MAX, |x| x as u32);
 
-        Ok(Parser {
+        Parser {
             reader,
             field_reader: FieldReader::default(),
             observers: Vec::default(),
+            next_observer_id: 0,
 
             #[cfg(feature = "dota")]
             combat_log: VecDeque::default(),
 
             prologue_completed: false,
-            skip_deltas: false,
+            keyframes: KeyframeIndex::default(),
+
+            skip_entities: false,
+            property_tracking: PropertyTracking::Disabled,
+            string_tables_filter: None,
+
+            lenient: false,
+            corruption: None,
+            message_offset: 0,
+            skipping: false,
+
+            progress_callback: None,
+            cancellation: CancellationToken::default(),
+            stats: None,
 
             replay_info,
             last_tick,
 
             context: Context::default(),
-        })
+        }
     }
 
     pub fn context(&self) -> &Context {
@@ -73,15 +173,241 @@ impl<'a> Parser<'a> {
         &self.replay_info
     }
 
-    /// Registers new observer and returns `Rc<RefCell<T>>` of it.
-    /// Observer struct must implement Observer and Default traits.
-    pub fn register_observer<T>(&mut self) -> Rc<RefCell<T>>
+    /// Returns index of full packets. Unless [`Parser::build_keyframe_index`]
+    /// was called, it contains only full packets the parser has gone through.
+    pub fn keyframes(&self) -> &KeyframeIndex {
+        &self.keyframes
+    }
+
+    /// Scans the whole replay for full packets without decoding them. Called
+    /// automatically by [`DemoRunner::jump_to_tick`]. Scan of truncated
+    /// replay stops at the last complete message.
+    pub fn build_keyframe_index(&mut self) -> Result<&KeyframeIndex, ParserError> {
+        if !self.keyframes.scanned {
+            self.keyframes = self.reader.read_keyframe_index()?;
+        }
+        Ok(&self.keyframes)
+    }
+
+    /// Writes complete keyframe index to a sidecar file (see
+    /// [`KeyframeIndex::sidecar_path`]), so other processes can seek into the
+    /// same replay without scanning it. Index of truncated replay is
+    /// rejected with [`KeyframeIndexError::Incomplete`].
+    pub fn save_keyframe_index(&mut self, path: impl AsRef<Path>) -> Result<(), ParserError> {
+        let replay_len = self
+            .reader
+            .replay_len()
+            .ok_or(ParserError::SeekNotSupported)?;
+        let playback_ticks = self.replay_info.playback_ticks() as u32;
+        let index = self.build_keyframe_index()?;
+        if !index.is_complete() {
+            return Err(KeyframeIndexError::Incomplete.into());
+        }
+        let buf = index.encode(replay_len, playback_ticks);
+        std::fs::write(path, buf)?;
+        Ok(())
+    }
+
+    /// Loads keyframe index saved by [`Parser::save_keyframe_index`]. Index
+    /// is rejected with [`KeyframeIndexError::Stale`] if it was built for a
+    /// replay with different length or playback ticks.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// use source2_demo::prelude::*;
+    ///
+    /// # fn main() -> Result<(), Box<dyn std::error::Error>> {
+    /// let replay = std::fs::read("match.dem")?;
+    /// let index_path = KeyframeIndex::sidecar_path("match.dem");
+    ///
+    /// let mut parser = Parser::new(&replay)?;
+    /// if parser.load_keyframe_index(&index_path).is_err() {
+    ///     parser.save_keyframe_index(&index_path)?;
+    /// }
+    ///
+    /// parser.jump_to_tick(30000)?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn load_keyframe_index(&mut self, path: impl AsRef<Path>) -> Result<(), ParserError> {
+        let replay_len = self
+            .reader
+            .replay_len()
+            .ok_or(ParserError::SeekNotSupported)?;
+        let playback_ticks = self.replay_info.playback_ticks() as u32;
+        self.keyframes =
+            KeyframeIndex::decode(&std::fs::read(path)?, replay_len, playback_ticks)?;
+        Ok(())
+    }
+
+    /// Disables decoding of entities. [`Observer::on_entity`] is never called
+    /// and [`Context::entities`] stays empty, which makes passes that only
+    /// need combat log, chat or game events several times faster.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// use source2_demo::prelude::*;
+    ///
+    /// # fn main() -> Result<(), Box<dyn std::error::Error>> {
+    /// let replay = std::fs::read("replay.dem")?;
+    ///
+    /// let mut parser = Parser::new(&replay)?;
+    /// parser.skip_entities(true);
+    /// parser.decode_string_tables(&["CombatLogNames"]);
+    /// parser.run_to_end()?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn skip_entities(&mut self, skip: bool) {
+        self.skip_entities = skip;
+    }
+
+    /// Decodes only string tables with given names. Other tables are created
+    /// without rows and [`Observer::on_string_table`] is not called for them.
+    /// `instancebaseline` is always decoded unless entities are skipped.
+    pub fn decode_string_tables(&mut self, names: &[&str]) {
+        self.string_tables_filter = Some(names.iter().map(|&name| name.into()).collect());
+    }
+
+    pub(crate) fn is_string_table_decoded(&self, name: &str) -> bool {
+        if name == "instancebaseline" && !self.skip_entities {
+            return true;
+        }
+
+        self.string_tables_filter
+            .as_ref()
+            .map_or(true, |filter| filter.contains(name))
+    }
+
+    /// Enables lenient mode. When replay turns out to be truncated or
+    /// corrupted, parser stops at the last good tick instead of returning
+    /// [`ParserError::Corrupted`]: observers get [`Observer::on_stop`] and
+    /// [`Observer::on_tick_end`] as if the replay ended there, and the error
+    /// is available from [`Parser::corruption`]. Observer errors and
+    /// corruption found while skipping to a tick with
+    /// [`DemoRunner::jump_to_tick`] are always returned.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// use source2_demo::prelude::*;
+    ///
+    /// # fn main() -> Result<(), Box<dyn std::error::Error>> {
+    /// let replay = std::fs::read("replay.dem")?;
+    ///
+    /// let mut parser = Parser::new(&replay)?;
+    /// parser.lenient(true);
+    /// parser.run_to_end()?;
+    ///
+    /// if let Some(e) = parser.corruption() {
+    ///     eprintln!("Replay is incomplete: {e}");
+    /// }
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn lenient(&mut self, lenient: bool) {
+        self.lenient = lenient;
+    }
+
+    /// Returns error which stopped parsing in lenient mode.
+    pub fn corruption(&self) -> Option<&ParserError> {
+        self.corruption.as_ref()
+    }
+
+    /// Registers new observer created with [`Default`] and returns handle of
+    /// it.
+    pub fn register_observer<T>(&mut self) -> ObserverHandle<T>
+    where
+        T: Observer + MaybeSend + Default + 'a,
+    {
+        self.add_observer(T::default())
+    }
+
+    /// Registers already constructed observer with priority `0`.
+    pub fn add_observer<T>(&mut self, observer: T) -> ObserverHandle<T>
+    where
+        T: Observer + MaybeSend + 'a,
+    {
+        self.add_observer_with_priority(observer, 0)
+    }
+
+    /// Registers observer with given priority. Observers with higher priority
+    /// are called first, so observer that depends on state derived by
+    /// another one should get lower priority. Observers with equal priority
+    /// are called in registration order.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// use source2_demo::prelude::*;
+    ///
+    /// #[derive(Default)]
+    /// struct Heroes;
+    ///
+    /// impl Observer for Heroes {}
+    ///
+    /// struct Report {
+    ///     heroes: ObserverHandle<Heroes>,
+    /// }
+    ///
+    /// impl Observer for Report {
+    ///     fn on_tick_end(&mut self, ctx: &Context) -> ObserverResult {
+    ///         // Heroes is already updated for this tick
+    ///         let heroes = self.heroes.borrow();
+    ///         Ok(())
+    ///     }
+    /// }
+    ///
+    /// # fn main() -> Result<(), Box<dyn std::error::Error>> {
+    /// let replay = std::fs::read("replay.dem")?;
+    /// let mut parser = Parser::new(&replay)?;
+    ///
+    /// let heroes = parser.add_observer_with_priority(Heroes, 10);
+    /// let report = parser.add_observer(Report { heroes });
+    ///
+    /// parser.run_to_tick(1000)?;
+    /// parser.unregister_observer(&report);
+    /// parser.run_to_end()?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn add_observer_with_priority<T>(&mut self, observer: T, priority: i32) -> ObserverHandle<T>
     where
-        T: Observer + Default + 'a,
+        T: Observer + MaybeSend + 'a,
     {
-        let rc = Rc::new(RefCell::new(T::default()));
-        self.observers.push(rc.clone());
-        rc.clone()
+        let id = self.next_observer_id;
+        self.next_observer_id += 1;
+
+        self.property_tracking = self.property_tracking.max(observer.property_tracking());
+
+        let observer = share(observer);
+        let position = self.observers.partition_point(|entry| entry.priority >= priority);
+        self.observers.insert(
+            position,
+            ObserverEntry {
+                id,
+                priority,
+                observer: observer.clone(),
+            },
+        );
+
+        ObserverHandle { id, observer }
+    }
+
+    /// Removes observer, it gets no more callbacks. Returns `false` if the
+    /// observer is already unregistered.
+    pub fn unregister_observer<T>(&mut self, handle: &ObserverHandle<T>) -> bool {
+        let len = self.observers.len();
+        self.observers.retain(|entry| entry.id != handle.id);
+        self.property_tracking = self
+            .observers
+            .iter()
+            .map(|entry| lock(&entry.observer).property_tracking())
+            .max()
+            .unwrap_or_default();
+        self.observers.len() != len
     }
 
     pub(crate) fn prologue(&mut self) -> Result<(), ParserError> {
@@ -89,7 +415,7 @@ impl<'a> Parser<'a> {
             return Ok(());
         }
 
-        while let Some(message) = self.reader.read_next_message()? {
+        while let Some(message) = self.read_next_message()? {
             if self.prologue_completed
                 && (message.msg_type == EDemoCommands::DemSendTables
                     || message.msg_type == EDemoCommands::DemClassInfo)
@@ -108,12 +434,62 @@ impl<'a> Parser<'a> {
         Ok(())
     }
 
+    pub(crate) fn read_next_message(&mut self) -> Result<Option<OuterMessage>, ParserError> {
+        if self.corruption.is_some() {
+            return Ok(None);
+        }
+
+        if self.cancellation.is_cancelled() {
+            return Err(ParserError::Cancelled);
+        }
+
+        let offset = self.reader.position();
+        let message = match self.reader.read_next_message() {
+            Ok(message) => message,
+            Err(e) => {
+                let e = self.corrupted(offset, e);
+                self.recover(e)?;
+                return Ok(None);
+            }
+        };
+
+        if let Some(message) = message.as_ref() {
+            self.message_offset = message.offset;
+            self.report_progress(message.tick);
+            if message.msg_type == EDemoCommands::DemFullPacket {
+                self.keyframes.insert(Keyframe {
+                    tick: message.tick,
+                    offset: message.offset,
+                });
+            }
+        }
+
+        Ok(message)
+    }
+
     pub(crate) fn on_demo_command(
         &mut self,
         msg_type: EDemoCommands,
         msg: &[u8],
     ) -> Result<(), ParserError> {
+        let started = self.stats.is_some().then(Instant::now);
+        if let Err(e) = self.apply_demo_command(msg_type, msg) {
+            let e = self.corrupted(self.message_offset, e);
+            return self.recover(e);
+        }
+        if let (Some(stats), Some(started)) = (self.stats.as_mut(), started) {
+            stats.add_message(ReplayMessage::Demo(msg_type), msg.len(), started.elapsed());
+        }
+
+        try_observers!(self, on_demo_command(&self.context, msg_type, msg))?;
+        Ok(())
+    }
+
+    fn apply_demo_command(&mut self, msg_type: EDemoCommands, msg: &[u8]) -> Result<(), ParserError> {
         match msg_type {
+            EDemoCommands::DemFileHeader => {
+                self.dem_file_header(CDemoFileHeader::decode(msg)?)?;
+            }
             EDemoCommands::DemSendTables => {
                 self.dem_send_tables(CDemoSendTables::decode(msg)?)?;
             }
@@ -130,13 +506,47 @@ impl<'a> Parser<'a> {
             EDemoCommands::DemStop => {
                 self.dem_stop()?;
             }
+            EDemoCommands::DemFileInfo => {
+                self.dem_file_info(CDemoFileInfo::decode(msg)?)?;
+            }
             _ => {}
         };
-
-        try_observers!(self, on_demo_command(&self.context, msg_type, msg))?;
         Ok(())
     }
 
+    /// Attaches position to decoding errors. Observer errors and I/O errors
+    /// unrelated to replay content are returned as is.
+    pub(crate) fn corrupted(&self, offset: usize, error: ParserError) -> ParserError {
+        match error {
+            ParserError::ObserverError(_) | ParserError::Corrupted { .. } => error,
+            ParserError::Io(ref e)
+                if !matches!(
+                    e.kind(),
+                    std::io::ErrorKind::UnexpectedEof | std::io::ErrorKind::InvalidData
+                ) =>
+            {
+                error
+            }
+            _ => ParserError::Corrupted {
+                offset,
+                tick: self.context.tick,
+                source: Box::new(error),
+            },
+        }
+    }
+
+    /// In lenient mode stores corruption error and stops parsing,
+    /// otherwise returns the error. Observers are detached while skipping,
+    /// so the error is returned as they can't be notified.
+    fn recover(&mut self, error: ParserError) -> Result<(), ParserError> {
+        if !self.lenient || self.skipping || !matches!(error, ParserError::Corrupted { .. }) {
+            return Err(error);
+        }
+
+        self.corruption = Some(error);
+        self.on_stop()
+    }
+
     #[cfg(feature = "deadlock")]
     pub fn deadlock_match_details(&mut self) -> Result<CMsgMatchMetaDataContents, ParserError> {
         self.reader.read_deadlock_match_details()
@@ -1,6 +1,14 @@
 use crate::parser::Context;
 use crate::proto::*;
-use crate::{Entity, EntityEvents, GameEvent, StringTable};
+use crate::{Entity, EntityEvents, GameEvent, PropertyChange, PropertyTracking, StringTable};
+use std::ops::{Deref, DerefMut};
+
+#[cfg(not(feature = "send"))]
+use std::cell::RefCell;
+#[cfg(not(feature = "send"))]
+use std::rc::Rc;
+#[cfg(feature = "send")]
+use std::sync::{Arc, Mutex, PoisonError};
 
 #[cfg(feature = "dota")]
 use crate::event::CombatLogEntry;
@@ -10,7 +18,7 @@ pub type ObserverResult = anyhow::Result<()>;
 
 /// A trait defining methods for handling game event and protobuf messages. Can
 /// be attached to [`crate::Parser`] instance with [`crate::Parser::register_observer`]
-/// method.
+/// or [`crate::Parser::add_observer`] methods.
 #[allow(unused_variables)]
 pub trait Observer {
     fn on_demo_command(
@@ -70,6 +78,26 @@ pub trait Observer {
         Ok(())
     }
 
+    /// Called after [`Observer::on_entity`] for every property written by
+    /// entity creation or update. Enabled only for parsers with at least one
+    /// observer requesting [`Observer::property_tracking`].
+    fn on_property_change(
+        &mut self,
+        ctx: &Context,
+        entity: &Entity,
+        change: &PropertyChange,
+    ) -> ObserverResult {
+        Ok(())
+    }
+
+    /// Property changes parser should record for
+    /// [`Observer::on_property_change`] and [`Context::property_changes`].
+    /// Checked once on registration, parser records the most detailed
+    /// tracking requested by its observers.
+    fn property_tracking(&self) -> PropertyTracking {
+        PropertyTracking::Disabled
+    }
+
     fn on_game_event(&mut self, ctx: &Context, ge: &GameEvent) -> ObserverResult {
         Ok(())
     }
@@ -87,6 +115,12 @@ pub trait Observer {
         Ok(())
     }
 
+    /// Called for packet messages with id which doesn't belong to any known
+    /// message enum, or belongs to a game other than the detected one.
+    fn on_unknown_message(&mut self, ctx: &Context, msg_type: i32, msg: &[u8]) -> ObserverResult {
+        Ok(())
+    }
+
     #[cfg(feature = "dota")]
     fn on_combat_log(&mut self, ctx: &Context, cle: &CombatLogEntry) -> ObserverResult {
         Ok(())
@@ -122,3 +156,117 @@ pub trait Observer {
         Ok(())
     }
 }
+
+/// `Send` if `send` feature is enabled, implemented for all types otherwise.
+/// Bound of observers and callbacks stored in [`crate::Parser`].
+#[cfg(feature = "send")]
+pub trait MaybeSend: Send {}
+
+#[cfg(feature = "send")]
+impl<T: Send + ?Sized> MaybeSend for T {}
+
+/// `Send` if `send` feature is enabled, implemented for all types otherwise.
+/// Bound of observers and callbacks stored in [`crate::Parser`].
+#[cfg(not(feature = "send"))]
+pub trait MaybeSend {}
+
+#[cfg(not(feature = "send"))]
+impl<T: ?Sized> MaybeSend for T {}
+
+/// Shared observer, `Arc<Mutex<T>>` with `send` feature and `Rc<RefCell<T>>`
+/// without it.
+#[cfg(not(feature = "send"))]
+pub(crate) type Shared<T> = Rc<RefCell<T>>;
+
+#[cfg(feature = "send")]
+pub(crate) type Shared<T> = Arc<Mutex<T>>;
+
+#[cfg(not(feature = "send"))]
+pub(crate) type DynObserver<'a> = dyn Observer + 'a;
+
+#[cfg(feature = "send")]
+pub(crate) type DynObserver<'a> = dyn Observer + Send + 'a;
+
+#[cfg(not(feature = "send"))]
+pub(crate) fn share<T>(value: T) -> Shared<T> {
+    Rc::new(RefCell::new(value))
+}
+
+#[cfg(feature = "send")]
+pub(crate) fn share<T>(value: T) -> Shared<T> {
+    Arc::new(Mutex::new(value))
+}
+
+#[cfg(not(feature = "send"))]
+pub(crate) fn lock<T: ?Sized>(shared: &Shared<T>) -> impl DerefMut<Target = T> + '_ {
+    shared.borrow_mut()
+}
+
+#[cfg(feature = "send")]
+pub(crate) fn lock<T: ?Sized>(shared: &Shared<T>) -> impl DerefMut<Target = T> + '_ {
+    shared.lock().unwrap_or_else(PoisonError::into_inner)
+}
+
+/// Handle of observer registered in [`crate::Parser`]. Gives access to the
+/// observer and removes it with [`crate::Parser::unregister_observer`].
+pub struct ObserverHandle<T> {
+    pub(crate) id: usize,
+    pub(crate) observer: Shared<T>,
+}
+
+impl<T> ObserverHandle<T> {
+    /// Immutably borrows the observer. With `send` feature it locks the
+    /// observer, so the guard must not be held while parser runs.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the observer is currently mutably borrowed.
+    #[cfg(not(feature = "send"))]
+    pub fn borrow(&self) -> impl Deref<Target = T> + '_ {
+        self.observer.borrow()
+    }
+
+    /// Immutably borrows the observer. With `send` feature it locks the
+    /// observer, so the guard must not be held while parser runs.
+    #[cfg(feature = "send")]
+    pub fn borrow(&self) -> impl Deref<Target = T> + '_ {
+        lock(&self.observer)
+    }
+
+    /// Mutably borrows the observer.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the observer is currently borrowed without `send` feature.
+    pub fn borrow_mut(&self) -> impl DerefMut<Target = T> + '_ {
+        lock(&self.observer)
+    }
+
+    /// Returns observer if it's unregistered and this is the last handle.
+    #[cfg(not(feature = "send"))]
+    pub fn into_inner(self) -> Option<T> {
+        Rc::into_inner(self.observer).map(RefCell::into_inner)
+    }
+
+    /// Returns observer if it's unregistered and this is the last handle.
+    #[cfg(feature = "send")]
+    pub fn into_inner(self) -> Option<T> {
+        Arc::into_inner(self.observer)
+            .map(|observer| observer.into_inner().unwrap_or_else(PoisonError::into_inner))
+    }
+}
+
+impl<T> Clone for ObserverHandle<T> {
+    fn clone(&self) -> Self {
+        ObserverHandle {
+            id: self.id,
+            observer: self.observer.clone(),
+        }
+    }
+}
+
+pub(crate) struct ObserverEntry<'a> {
+    pub(crate) id: usize,
+    pub(crate) priority: i32,
+    pub(crate) observer: Shared<DynObserver<'a>>,
+}
@@ -0,0 +1,231 @@
+use crate::error::ParserError;
+use crate::parser::demo::{DemoCommands, DemoMessages};
+use crate::parser::{Context, Keyframe, Observer, Parser};
+use crate::proto::*;
+use std::mem;
+use std::num::NonZeroUsize;
+use std::thread;
+
+/// Parses replay on several threads. Replay is split into segments at
+/// [`CDemoFullPacket`] boundaries, every segment is parsed by its own
+/// [`Parser`] seeded from the full packet it starts with. Prologue (send
+/// tables, class info and string tables) is parsed only once and shared
+/// between workers.
+///
+/// Every segment gets its own observer. [`ParallelRunner::run_merged`]
+/// merges them in tick order with [`Merge`], [`ParallelRunner::run`] returns
+/// them in tick order as they are. Observer of a segment sees the same
+/// callbacks it would see in a single-threaded run, except entity
+/// [`Created`](crate::EntityEvents::Created) events of the seeding full
+/// packet.
+///
+/// Segment ends right before the full packet the next segment starts with.
+/// If messages of the full packet's tick precede it, both segments get
+/// `on_tick_start` and `on_tick_end` of that tick, each with its own part of
+/// the messages.
+///
+/// # Examples
+///
+/// ```no_run
+/// use source2_demo::prelude::*;
+///
+/// #[derive(Default)]
+/// struct Ticks {
+///     count: usize,
+/// }
+///
+/// impl Observer for Ticks {
+///     fn on_tick_end(&mut self, ctx: &Context) -> ObserverResult {
+///         self.count += 1;
+///         Ok(())
+///     }
+/// }
+///
+/// impl Merge for Ticks {
+///     fn merge(&mut self, next: Self) {
+///         self.count += next.count;
+///     }
+/// }
+///
+/// # fn main() -> Result<(), Box<dyn std::error::Error>> {
+/// let replay = std::fs::read("replay.dem")?;
+///
+/// let ticks = ParallelRunner::new(&replay)
+///     .threads(4)
+///     .run_merged(Ticks::default)?
+///     .count;
+/// # Ok(())
+/// # }
+/// ```
+pub struct ParallelRunner<'a> {
+    replay: &'a [u8],
+    threads: usize,
+}
+
+/// Combines observers of consecutive segments of [`ParallelRunner`].
+pub trait Merge {
+    /// Appends results of the segment following `self`.
+    fn merge(&mut self, next: Self);
+}
+
+struct Segment {
+    keyframe: Option<Keyframe>,
+    start: usize,
+    end: usize,
+}
+
+impl<'a> ParallelRunner<'a> {
+    /// Creates runner using all available cores.
+    pub fn new(replay: &'a [u8]) -> Self {
+        ParallelRunner {
+            replay,
+            threads: thread::available_parallelism().map_or(1, NonZeroUsize::get),
+        }
+    }
+
+    /// Sets number of worker threads.
+    pub fn threads(mut self, threads: usize) -> Self {
+        self.threads = threads.max(1);
+        self
+    }
+
+    /// Parses replay to the end and merges observers of all segments in tick
+    /// order, `factory` is called once per segment.
+    pub fn run_merged<T, F>(&self, factory: F) -> Result<T, ParserError>
+    where
+        T: Observer + Merge + Send,
+        F: Fn() -> T + Sync,
+    {
+        let mut observers = self.run(&factory)?.into_iter();
+        let mut merged = observers.next().unwrap_or_else(&factory);
+        for observer in observers {
+            merged.merge(observer);
+        }
+        Ok(merged)
+    }
+
+    /// Parses replay to the end, `factory` is called once per segment.
+    /// Observers are returned in tick order.
+    pub fn run<T, F>(&self, factory: F) -> Result<Vec<T>, ParserError>
+    where
+        T: Observer + Send,
+        F: Fn() -> T + Sync,
+    {
+        let mut parser = Parser::new(self.replay)?;
+        parser.prologue()?;
+
+        let start = parser.reader.position();
+        parser.build_keyframe_index()?;
+
+        let segments = self.split(start, &parser.keyframes.keyframes);
+        let context = mem::take(&mut parser.context);
+        let factory = &factory;
+
+        thread::scope(|scope| {
+            let handles = segments
+                .into_iter()
+                .map(|segment| {
+                    let context = context.clone();
+                    scope.spawn(move || self.run_segment(context, segment, factory()))
+                })
+                .collect::<Vec<_>>();
+
+            handles
+                .into_iter()
+                .map(|handle| {
+                    handle
+                        .join()
+                        .unwrap_or_else(|e| std::panic::resume_unwind(e))
+                })
+                .collect()
+        })
+    }
+
+    /// Picks full packets closest to equal byte ranges. The first full
+    /// packet is never a boundary, it's applied with observers as in a
+    /// normal run.
+    fn split(&self, start: usize, keyframes: &[Keyframe]) -> Vec<Segment> {
+        let candidates = keyframes.get(1..).unwrap_or_default();
+        let len = self.replay.len().saturating_sub(start);
+
+        let mut boundaries: Vec<Keyframe> = vec![];
+        for i in 1..self.threads {
+            let target = start + len * i / self.threads;
+            let idx = candidates.partition_point(|kf| kf.offset < target);
+            if let Some(&kf) = candidates.get(idx) {
+                if boundaries.last().map_or(true, |last| last.offset < kf.offset) {
+                    boundaries.push(kf);
+                }
+            }
+        }
+
+        let mut segments = vec![Segment {
+            keyframe: None,
+            start,
+            end: usize::MAX,
+        }];
+        for kf in boundaries {
+            segments.last_mut().unwrap().end = kf.offset;
+            segments.push(Segment {
+                keyframe: Some(kf),
+                start: kf.offset,
+                end: usize::MAX,
+            });
+        }
+        segments
+    }
+
+    fn run_segment<T: Observer + Send>(
+        &self,
+        context: Context,
+        segment: Segment,
+        observer: T,
+    ) -> Result<T, ParserError> {
+        let mut parser = Parser::new(self.replay)?;
+        parser.context = context;
+        parser.prologue_completed = true;
+        let observer = parser.add_observer(observer);
+        parser.run_segment(segment)?;
+        drop(parser);
+
+        Ok(observer.into_inner().expect("parser is dropped"))
+    }
+}
+
+impl Parser<'_> {
+    fn run_segment(&mut self, segment: Segment) -> Result<(), ParserError> {
+        self.reader.reset_to(segment.start)?;
+
+        if segment.keyframe.is_some() {
+            let message = self
+                .read_next_message()?
+                .ok_or(ParserError::ReplayEncodingError)?;
+
+            let (tick, previous_tick) = (self.context.tick, self.context.previous_tick);
+            self.context.tick = message.tick;
+
+            let observers = mem::take(&mut self.observers);
+            let seeded = CDemoFullPacket::decode(message.buf.as_slice())
+                .map_err(ParserError::from)
+                .and_then(|full_packet| self.dem_full_packet(full_packet));
+            self.observers = observers;
+            seeded?;
+
+            self.context.tick = tick;
+            self.context.previous_tick = previous_tick;
+            self.on_tick_start(message.tick)?;
+            self.on_demo_command(message.msg_type, message.buf.as_slice())?;
+        }
+
+        while self.reader.position() < segment.end {
+            let Some(message) = self.read_next_message()? else {
+                break;
+            };
+            self.on_tick_start(message.tick)?;
+            self.on_demo_command(message.msg_type, message.buf.as_slice())?;
+        }
+        self.on_tick_end()?;
+
+        Ok(())
+    }
+}
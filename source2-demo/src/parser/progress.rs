@@ -0,0 +1,142 @@
+use crate::parser::{MaybeSend, Parser};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+
+#[cfg(not(feature = "send"))]
+pub(crate) type ProgressCallback<'a> = Box<dyn FnMut(&Progress) + 'a>;
+
+#[cfg(feature = "send")]
+pub(crate) type ProgressCallback<'a> = Box<dyn FnMut(&Progress) + Send + 'a>;
+
+/// Parsing progress passed to callback set with [`Parser::on_progress`].
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+pub struct Progress {
+    pub(crate) tick: u32,
+    pub(crate) total_ticks: Option<u32>,
+    pub(crate) bytes: usize,
+    pub(crate) total_bytes: Option<usize>,
+}
+
+impl Progress {
+    /// Tick of the last read outer message.
+    pub fn tick(&self) -> u32 {
+        self.tick
+    }
+
+    /// Playback ticks from [`Parser::replay_info`], `None` for streamed
+    /// replays.
+    pub fn total_ticks(&self) -> Option<u32> {
+        self.total_ticks
+    }
+
+    /// Bytes consumed from the start of replay.
+    pub fn bytes(&self) -> usize {
+        self.bytes
+    }
+
+    /// Replay length, `None` for streamed replays.
+    pub fn total_bytes(&self) -> Option<usize> {
+        self.total_bytes
+    }
+
+    /// Consumed part of replay in range `0.0..=1.0`, based on bytes.
+    pub fn fraction(&self) -> Option<f32> {
+        self.total_bytes
+            .filter(|&total| total > 0)
+            .map(|total| (self.bytes as f64 / total as f64).min(1.0) as f32)
+    }
+}
+
+/// Cancels parsing from another thread. Parser checks the token before
+/// every outer message and returns [`ParserError::Cancelled`](crate::error::ParserError::Cancelled)
+/// once it's cancelled.
+#[derive(Debug, Clone, Default)]
+pub struct CancellationToken(Arc<AtomicBool>);
+
+impl CancellationToken {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn cancel(&self) {
+        self.0.store(true, Ordering::Relaxed);
+    }
+
+    pub fn is_cancelled(&self) -> bool {
+        self.0.load(Ordering::Relaxed)
+    }
+}
+
+impl<'a> Parser<'a> {
+    /// Sets callback which is called after every outer message is read.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// use source2_demo::prelude::*;
+    ///
+    /// # fn main() -> Result<(), Box<dyn std::error::Error>> {
+    /// let replay = std::fs::read("replay.dem")?;
+    ///
+    /// let mut parser = Parser::new(&replay)?;
+    /// parser.on_progress(|progress| {
+    ///     if let Some(fraction) = progress.fraction() {
+    ///         eprint!("\r{:.1}%", fraction * 100.0);
+    ///     }
+    /// });
+    /// parser.run_to_end()?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn on_progress(&mut self, callback: impl FnMut(&Progress) + MaybeSend + 'a) {
+        self.progress_callback = Some(Box::new(callback));
+    }
+
+    /// Returns token which cancels this parser. Cancelled parser returns
+    /// [`ParserError::Cancelled`](crate::error::ParserError::Cancelled) from
+    /// the current run.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// use source2_demo::prelude::*;
+    ///
+    /// # fn main() -> Result<(), Box<dyn std::error::Error>> {
+    /// let replay = std::fs::read("replay.dem")?;
+    ///
+    /// let mut parser = Parser::new(&replay)?;
+    /// let token = parser.cancellation_token();
+    /// std::thread::spawn(move || {
+    ///     std::thread::sleep(std::time::Duration::from_secs(1));
+    ///     token.cancel();
+    /// });
+    /// parser.run_to_end()?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn cancellation_token(&self) -> CancellationToken {
+        self.cancellation.clone()
+    }
+
+    /// Replaces cancellation token, so one token can cancel several parsers.
+    pub fn set_cancellation_token(&mut self, token: CancellationToken) {
+        self.cancellation = token;
+    }
+
+    pub(crate) fn report_progress(&mut self, tick: u32) {
+        if self.progress_callback.is_none() {
+            return;
+        }
+
+        let progress = Progress {
+            tick,
+            total_ticks: (self.last_tick != u32::MAX).then_some(self.last_tick),
+            bytes: self.reader.position(),
+            total_bytes: self.reader.replay_len(),
+        };
+
+        if let Some(callback) = self.progress_callback.as_mut() {
+            callback(&progress);
+        }
+    }
+}
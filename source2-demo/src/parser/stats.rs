@@ -0,0 +1,174 @@
+use crate::parser::{Game, Parser, ReplayMessage};
+use crate::proto::*;
+use crate::EntityEvents;
+use hashbrown::HashMap;
+use std::time::Duration;
+
+/// Counters of a single message type.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct MessageStats {
+    pub(crate) count: u64,
+    pub(crate) bytes: u64,
+    pub(crate) time: Duration,
+}
+
+impl MessageStats {
+    pub fn count(&self) -> u64 {
+        self.count
+    }
+
+    /// Total size of message payloads.
+    pub fn bytes(&self) -> u64 {
+        self.bytes
+    }
+
+    /// Time spent handling messages, including observer callbacks. For
+    /// [`EDemoCommands::DemPacket`] and [`EDemoCommands::DemFullPacket`] it
+    /// includes handling of inner messages. Not measured for unknown
+    /// messages.
+    pub fn time(&self) -> Duration {
+        self.time
+    }
+}
+
+/// Entity counters of a single class.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct ClassStats {
+    pub(crate) created: u64,
+    pub(crate) updated: u64,
+    pub(crate) deleted: u64,
+    pub(crate) fields: u64,
+}
+
+impl ClassStats {
+    pub fn created(&self) -> u64 {
+        self.created
+    }
+
+    pub fn updated(&self) -> u64 {
+        self.updated
+    }
+
+    pub fn deleted(&self) -> u64 {
+        self.deleted
+    }
+
+    /// Number of decoded field values, baselines are not counted.
+    pub fn fields(&self) -> u64 {
+        self.fields
+    }
+}
+
+/// Parse statistics collected when enabled with [`Parser::collect_stats`].
+/// Implements [`Display`](std::fmt::Display) as a table report.
+#[derive(Debug, Clone, Default)]
+pub struct ParserStats {
+    pub(crate) messages: HashMap<ReplayMessage, MessageStats>,
+    pub(crate) classes: HashMap<Box<str>, ClassStats>,
+}
+
+impl ParserStats {
+    /// Iterator over counters of demo commands, svc messages, game specific
+    /// user messages and unknown messages.
+    pub fn messages(&self) -> impl Iterator<Item = (ReplayMessage, &MessageStats)> {
+        self.messages
+            .iter()
+            .map(|(&msg_type, stats)| (msg_type, stats))
+    }
+
+    /// Iterator over counters of packet messages with unknown ids. Non-empty
+    /// result usually means protobufs are outdated.
+    pub fn unknown_messages(&self) -> impl Iterator<Item = (i32, &MessageStats)> {
+        self.messages.iter().filter_map(|(msg_type, stats)| match msg_type {
+            ReplayMessage::Unknown(msg_type) => Some((*msg_type, stats)),
+            _ => None,
+        })
+    }
+
+    /// Iterator over entity counters by class name.
+    pub fn classes(&self) -> impl Iterator<Item = (&str, &ClassStats)> {
+        self.classes
+            .iter()
+            .map(|(name, stats)| (name.as_ref(), stats))
+    }
+
+    pub(crate) fn add_message(&mut self, msg_type: ReplayMessage, bytes: usize, time: Duration) {
+        let stats = self.messages.entry(msg_type).or_default();
+        stats.count += 1;
+        stats.bytes += bytes as u64;
+        stats.time += time;
+    }
+
+    pub(crate) fn add_entity(&mut self, class_name: &str, event: EntityEvents, fields: usize) {
+        let stats = self.classes.entry_ref(class_name).or_default();
+        match event {
+            EntityEvents::Created => stats.created += 1,
+            EntityEvents::Updated => stats.updated += 1,
+            EntityEvents::Deleted => stats.deleted += 1,
+        }
+        stats.fields += fields as u64;
+    }
+}
+
+impl ReplayMessage {
+    /// Returns type of message found in [`CDemoPacket`], `None` for types
+    /// not tracked by [`ParserStats`].
+    #[cfg_attr(not(any(feature = "dota", feature = "deadlock")), allow(unused_variables))]
+    pub(crate) fn from_packet_message(msg_type: i32, game: Game) -> Option<Self> {
+        #[cfg(feature = "dota")]
+        if game != Game::Deadlock {
+            if let Ok(msg) = EDotaUserMessages::try_from(msg_type) {
+                return Some(ReplayMessage::DotaUser(msg));
+            }
+        }
+
+        #[cfg(feature = "deadlock")]
+        if game != Game::Dota {
+            if let Ok(msg) = CitadelUserMessageIds::try_from(msg_type) {
+                return Some(ReplayMessage::CitadelUser(msg));
+            } else if ECitadelGameEvents::try_from(msg_type).is_ok() {
+                return None;
+            }
+        }
+
+        SvcMessages::try_from(msg_type).ok().map(ReplayMessage::Svc)
+    }
+}
+
+impl Parser<'_> {
+    /// Enables collection of [`ParserStats`]: message counts, sizes and
+    /// handling time by type, and entity events and decoded fields by class.
+    /// Disabled by default since timing every message has a cost.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// use source2_demo::prelude::*;
+    ///
+    /// # fn main() -> Result<(), Box<dyn std::error::Error>> {
+    /// let replay = std::fs::read("replay.dem")?;
+    ///
+    /// let mut parser = Parser::new(&replay)?;
+    /// parser.collect_stats(true);
+    /// parser.run_to_end()?;
+    ///
+    /// if let Some(stats) = parser.stats() {
+    ///     println!("{stats}");
+    /// }
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn collect_stats(&mut self, enabled: bool) {
+        if !enabled {
+            self.stats = None;
+        } else if self.stats.is_none() {
+            self.stats = Some(Box::default());
+        }
+    }
+
+    /// Returns collected statistics, `None` unless enabled with
+    /// [`Parser::collect_stats`].
+    pub fn stats(&self) -> Option<&ParserStats> {
+        self.stats.as_deref()
+    }
+}
@@ -0,0 +1,369 @@
+use crate::error::ParserError;
+use crate::proto::*;
+use crate::reader::*;
+use std::io::Write;
+use std::ops::Range;
+
+/// Writes new `PBDEMS2` replays from parts of existing one.
+///
+/// Clip consists of the original prologue (file header, signon packets, send
+/// tables and class info), a full packet synthesized at the start tick,
+/// original messages up to the end tick and [`CDemoFileInfo`] footer. Ticks
+/// are not rebased, so [`CDemoFileInfo::playback_ticks`] of the clip is its
+/// end tick.
+///
+/// Full packet is built from the nearest preceding full packet of the
+/// original replay followed by entity and string table updates up to the
+/// start tick. Other messages before the start tick (chat, combat log, game
+/// events) are dropped, the ones at the start tick follow the full packet.
+///
+/// # Examples
+///
+/// ```no_run
+/// use source2_demo::prelude::*;
+///
+/// # fn main() -> Result<(), Box<dyn std::error::Error>> {
+/// let replay = std::fs::read("replay.dem")?;
+///
+/// let clip = DemoWriter::new(&replay)?.clip(30000, 31800)?;
+/// std::fs::write("clip.dem", &clip)?;
+///
+/// let mut parser = Parser::new(&clip)?;
+/// parser.run_to_end()?;
+/// # Ok(())
+/// # }
+/// ```
+pub struct DemoWriter<'a> {
+    replay: &'a [u8],
+    replay_info: CDemoFileInfo,
+}
+
+impl<'a> DemoWriter<'a> {
+    pub fn new(replay: &'a [u8]) -> Result<Self, ParserError> {
+        let mut reader = Reader::new(replay);
+
+        if replay.len() < 16 || reader.read_bytes(8) != b"PBDEMS2\0" {
+            return Err(ParserError::WrongMagic);
+        }
+
+        let replay_info = reader.read_replay_info()?;
+
+        Ok(DemoWriter {
+            replay,
+            replay_info,
+        })
+    }
+
+    /// Returns new replay with ticks in range `start_tick..=end_tick`.
+    pub fn clip(&self, start_tick: u32, end_tick: u32) -> Result<Vec<u8>, ParserError> {
+        if start_tick >= end_tick {
+            return Err(ParserError::InvalidTickRange {
+                start: start_tick,
+                end: end_tick,
+            });
+        }
+
+        let mut reader = Reader::new(self.replay);
+        reader.reset_to(16);
+
+        let mut out = Vec::with_capacity(self.replay.len() / 4);
+        // Header is kept as is, only file info offset is patched at the end
+        out.extend_from_slice(&self.replay[..16]);
+
+        loop {
+            let offset = reader.position();
+            let message = reader
+                .read_next_message()?
+                .ok_or(ParserError::ReplayEncodingError)?;
+            out.extend_from_slice(&self.replay[offset..reader.position()]);
+            if message.msg_type == EDemoCommands::DemSyncTick {
+                break;
+            }
+        }
+
+        let prologue_end = reader.position();
+        if let Some(keyframe) = reader
+            .read_keyframe_index()?
+            .nearest(start_tick.saturating_sub(1))
+            .filter(|keyframe| keyframe.offset >= prologue_end)
+        {
+            reader.reset_to(keyframe.offset);
+        }
+
+        let mut string_tables = CDemoStringTables::default();
+        let mut packet = BitWriter::default();
+        let mut start_messages = Vec::new();
+        let mut full_packet_written = false;
+        let mut last_tick = start_tick;
+
+        loop {
+            let offset = reader.position();
+            let Some(message) = reader.read_next_message()? else {
+                break;
+            };
+
+            if matches!(
+                message.msg_type,
+                EDemoCommands::DemStop | EDemoCommands::DemFileInfo
+            ) || message.tick > end_tick
+            {
+                break;
+            }
+
+            if message.tick <= start_tick {
+                match message.msg_type {
+                    EDemoCommands::DemFullPacket => {
+                        let full_packet = CDemoFullPacket::decode(message.buf.as_slice())?;
+                        string_tables = full_packet
+                            .string_table
+                            .ok_or(ParserError::IncompleteFullPacket("string tables"))?;
+                        packet = BitWriter::default();
+                        copy_packet_messages(
+                            full_packet
+                                .packet
+                                .ok_or(ParserError::IncompleteFullPacket("packet"))?
+                                .data(),
+                            &mut packet,
+                            |_| true,
+                        );
+                    }
+                    EDemoCommands::DemPacket | EDemoCommands::DemSignonPacket => {
+                        let demo_packet = CDemoPacket::decode(message.buf.as_slice())?;
+                        copy_packet_messages(demo_packet.data(), &mut packet, is_state_message);
+
+                        if message.tick == start_tick {
+                            let mut rest = BitWriter::default();
+                            copy_packet_messages(demo_packet.data(), &mut rest, |msg_type| {
+                                !is_state_message(msg_type)
+                            });
+                            let rest = rest.into_bytes();
+                            if !rest.is_empty() {
+                                let demo_packet = CDemoPacket { data: Some(rest) };
+                                write_message(
+                                    &mut start_messages,
+                                    message.msg_type,
+                                    start_tick,
+                                    &demo_packet.encode_to_vec(),
+                                    false,
+                                )?;
+                            }
+                        }
+                    }
+                    _ if message.tick == start_tick => {
+                        start_messages.extend_from_slice(&self.replay[offset..reader.position()]);
+                    }
+                    _ => {}
+                }
+                continue;
+            }
+
+            if !full_packet_written {
+                write_full_packet(&mut out, start_tick, &mut string_tables, &mut packet)?;
+                out.append(&mut start_messages);
+                full_packet_written = true;
+            }
+
+            out.extend_from_slice(&self.replay[offset..reader.position()]);
+            last_tick = message.tick;
+        }
+
+        if !full_packet_written {
+            write_full_packet(&mut out, start_tick, &mut string_tables, &mut packet)?;
+            out.append(&mut start_messages);
+        }
+
+        write_message(&mut out, EDemoCommands::DemStop, last_tick, &[], false)?;
+
+        let file_info_offset = out.len() as u32;
+        write_message(
+            &mut out,
+            EDemoCommands::DemFileInfo,
+            last_tick,
+            &self.file_info(last_tick).encode_to_vec(),
+            false,
+        )?;
+        out[8..12].copy_from_slice(&file_info_offset.to_le_bytes());
+
+        Ok(out)
+    }
+
+    /// Writes clip returned by [`DemoWriter::clip`] to given writer.
+    pub fn write_clip<W: Write>(
+        &self,
+        start_tick: u32,
+        end_tick: u32,
+        mut writer: W,
+    ) -> Result<(), ParserError> {
+        writer.write_all(&self.clip(start_tick, end_tick)?)?;
+        Ok(())
+    }
+
+    /// Original file info with playback fields scaled to the clip.
+    fn file_info(&self, last_tick: u32) -> CDemoFileInfo {
+        let ticks = self.replay_info.playback_ticks().max(1) as f32;
+        let ratio = last_tick as f32 / ticks;
+
+        CDemoFileInfo {
+            playback_time: Some(self.replay_info.playback_time() * ratio),
+            playback_ticks: Some(last_tick as i32),
+            playback_frames: Some((self.replay_info.playback_frames() as f32 * ratio) as i32),
+            game_info: self.replay_info.game_info.clone(),
+        }
+    }
+}
+
+/// Messages which change entities or string tables.
+fn is_state_message(msg_type: u32) -> bool {
+    [
+        SvcMessages::SvcPacketEntities,
+        SvcMessages::SvcCreateStringTable,
+        SvcMessages::SvcUpdateStringTable,
+    ]
+    .iter()
+    .any(|&x| x as u32 == msg_type)
+}
+
+fn copy_packet_messages(data: &[u8], out: &mut BitWriter, filter: impl Fn(u32) -> bool) {
+    let mut reader = Reader::new(data);
+    while reader.bytes_remaining() != 0 {
+        let msg_type = reader.read_ubit_var();
+        let size = reader.read_var_u32();
+        let buf = reader.read_bytes(size);
+        if reader.is_overrun() {
+            break;
+        }
+
+        if filter(msg_type) {
+            out.write_ubit_var(msg_type);
+            out.write_var_u32(size);
+            out.write_bytes(&buf);
+        }
+    }
+}
+
+fn write_full_packet(
+    out: &mut Vec<u8>,
+    tick: u32,
+    string_tables: &mut CDemoStringTables,
+    packet: &mut BitWriter,
+) -> Result<(), ParserError> {
+    let full_packet = CDemoFullPacket {
+        string_table: Some(std::mem::take(string_tables)),
+        packet: Some(CDemoPacket {
+            data: Some(std::mem::take(packet).into_bytes()),
+        }),
+    };
+    write_message(
+        out,
+        EDemoCommands::DemFullPacket,
+        tick,
+        &full_packet.encode_to_vec(),
+        true,
+    )
+}
+
+pub(crate) fn write_message(
+    out: &mut Vec<u8>,
+    msg_type: EDemoCommands,
+    tick: u32,
+    buf: &[u8],
+    compress: bool,
+) -> Result<(), ParserError> {
+    let mut cmd = msg_type as u32;
+    let compressed;
+    let buf = if compress {
+        compressed = snap::raw::Encoder::new().compress_vec(buf)?;
+        cmd |= EDemoCommands::DemIsCompressed as u32;
+        compressed.as_slice()
+    } else {
+        buf
+    };
+
+    write_var_u32(out, cmd);
+    write_var_u32(out, tick);
+    write_var_u32(out, buf.len() as u32);
+    out.extend_from_slice(buf);
+
+    Ok(())
+}
+
+fn write_var_u32(out: &mut Vec<u8>, mut x: u32) {
+    while x >= 0x80 {
+        out.push(x as u8 | 0x80);
+        x >>= 7;
+    }
+    out.push(x as u8);
+}
+
+/// Little endian bit writer, counterpart of `BitsReader`.
+#[derive(Default)]
+pub(crate) struct BitWriter {
+    buf: Vec<u8>,
+    bits: usize,
+}
+
+impl BitWriter {
+    pub(crate) fn write_bits(&mut self, mut value: u32, mut amount: u32) {
+        while amount > 0 {
+            if self.bits % 8 == 0 {
+                self.buf.push(0);
+            }
+            let used = (self.bits % 8) as u32;
+            let n = (8 - used).min(amount);
+            let last = self.buf.len() - 1;
+            self.buf[last] |= ((value & ((1 << n) - 1)) as u8) << used;
+            value >>= n;
+            amount -= n;
+            self.bits += n as usize;
+        }
+    }
+
+    pub(crate) fn write_ubit_var(&mut self, x: u32) {
+        let (prefix, count) = match x >> 4 {
+            0 => (0, 0),
+            1..=0xf => (1, 4),
+            0x10..=0xff => (2, 8),
+            _ => (3, 28),
+        };
+        self.write_bits((x & 15) | (prefix << 4), 6);
+        self.write_bits(x >> 4, count);
+    }
+
+    pub(crate) fn write_var_u32(&mut self, x: u32) {
+        let mut buf = Vec::with_capacity(5);
+        write_var_u32(&mut buf, x);
+        self.write_bytes(&buf);
+    }
+
+    pub(crate) fn write_bytes(&mut self, bytes: &[u8]) {
+        if self.bits % 8 == 0 {
+            self.buf.extend_from_slice(bytes);
+            self.bits += bytes.len() * 8;
+        } else {
+            for &byte in bytes {
+                self.write_bits(byte as u32, 8);
+            }
+        }
+    }
+
+    /// Copies bits in given range of `src`.
+    pub(crate) fn write_bits_from(&mut self, src: &[u8], range: Range<usize>) {
+        let mut pos = range.start;
+        while pos < range.end {
+            if pos % 8 == 0 && self.bits % 8 == 0 && range.end - pos >= 8 {
+                let end = range.end / 8;
+                self.write_bytes(&src[pos / 8..end]);
+                pos = end * 8;
+                continue;
+            }
+            let used = pos % 8;
+            let n = (8 - used).min(range.end - pos);
+            self.write_bits((src[pos / 8] >> used) as u32, n as u32);
+            pos += n;
+        }
+    }
+
+    pub(crate) fn into_bytes(self) -> Vec<u8> {
+        self.buf
+    }
+}
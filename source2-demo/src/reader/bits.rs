@@ -51,11 +51,6 @@ const COORD_FACTOR: f32 = 1.0 / (1 << 5) as f32;
 impl BitsReader for Reader<'_> {
     #[inline]
     fn refill(&mut self) {
-        #[cfg(not(debug_assertions))]
-        unsafe {
-            self.le_reader.refill_lookahead_unchecked();
-        }
-        #[cfg(debug_assertions)]
         self.le_reader.refill_lookahead();
     }
 
@@ -68,7 +63,10 @@ impl BitsReader for Reader<'_> {
     #[inline(always)]
     fn read_bits_no_refill(&mut self, amount: u32) -> u32 {
         debug_assert!(amount <= 32);
-        debug_assert!(self.le_reader.has_bits_remaining(amount as usize));
+        if self.le_reader.lookahead_bits() < amount {
+            self.overrun = true;
+            return 0;
+        }
         let x = self.le_reader.peek(amount);
         self.le_reader.consume(amount);
         x as u32
@@ -76,6 +74,10 @@ impl BitsReader for Reader<'_> {
 
     #[inline]
     fn read_bytes(&mut self, amount: u32) -> Vec<u8> {
+        if !self.le_reader.has_bits_remaining(amount as usize * 8) {
+            self.overrun = true;
+            return vec![];
+        }
         let mut bytes = vec![0; amount as usize];
         self.le_reader.read_bytes(&mut bytes);
         bytes
@@ -205,7 +207,10 @@ impl BitsReader for Reader<'_> {
 
     #[inline]
     fn read_le_u64(&mut self) -> u64 {
-        self.le_reader.read_u64().unwrap()
+        self.le_reader.read_u64().unwrap_or_else(|| {
+            self.overrun = true;
+            0
+        })
     }
 
     #[inline]
@@ -213,7 +218,7 @@ impl BitsReader for Reader<'_> {
         let mut i = 0;
         loop {
             let b = self.read_bits(8) as u8;
-            if b == 0 {
+            if b == 0 || i == self.string_buf.len() {
                 return String::from_utf8_lossy(&self.string_buf[..i]).into();
             }
             self.string_buf[i] = b;
@@ -258,6 +263,10 @@ impl BitsReader for Reader<'_> {
     #[inline]
     fn read_bits_as_bytes(&mut self, n: u32) -> Vec<u8> {
         let bits = n % 8;
+        if !self.le_reader.has_bits_remaining(n as usize) {
+            self.overrun = true;
+            return vec![];
+        }
         let mut tmp = vec![0; (n >> 3) as usize];
         self.le_reader.read_bytes(&mut tmp);
         if bits > 0 {
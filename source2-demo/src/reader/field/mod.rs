@@ -4,8 +4,10 @@ mod op;
 use huff::*;
 use op::*;
 
-use crate::field::{FieldPath, FieldState, Serializer};
+use crate::error::SerializerError;
+use crate::field::{FieldDecoder, FieldPath, FieldState, Serializer};
 use crate::reader::{BitsReader, Reader};
+use crate::{PropertyChange, PropertyTracking};
 use std::cell::RefCell;
 
 pub(crate) struct FieldReader {
@@ -23,13 +25,43 @@ impl Default for FieldReader {
 }
 
 impl FieldReader {
+    /// Reads field paths and values into state, returns number of decoded
+    /// fields. Written fields are appended to `changes` unless tracking is
+    /// disabled.
     #[inline]
     pub(crate) fn read_fields(
         &self,
         reader: &mut Reader,
         serializer: &Serializer,
         state: &mut FieldState,
-    ) {
+        tracking: PropertyTracking,
+        changes: &mut Vec<PropertyChange>,
+    ) -> Result<usize, SerializerError> {
+        self.visit_fields(reader, serializer, |fp, decoder, reader| {
+            let value = decoder.decode(reader);
+            if tracking != PropertyTracking::Disabled {
+                changes.push(PropertyChange {
+                    path: *fp,
+                    previous: match tracking {
+                        PropertyTracking::Values => state.get_value(fp).cloned(),
+                        _ => None,
+                    },
+                    value: value.clone(),
+                });
+            }
+            state.set(fp, value);
+        })
+    }
+
+    /// Reads field paths and calls `visit` for every path with reader
+    /// positioned at the value, which must be consumed.
+    #[inline]
+    pub(crate) fn visit_fields(
+        &self,
+        reader: &mut Reader,
+        serializer: &Serializer,
+        mut visit: impl FnMut(&FieldPath, &FieldDecoder, &mut Reader),
+    ) -> Result<usize, SerializerError> {
         let mut paths = self.paths_buf.borrow_mut();
         let mut node = &self.tree;
         let mut i = 0;
@@ -47,6 +79,9 @@ impl FieldReader {
                     if let FieldOp::FieldPathEncodeFinish = op {
                         break;
                     }
+                    if reader.is_overrun() || i == paths.len() {
+                        return Err(SerializerError::InvalidFieldPath(fp.to_string()));
+                    }
                     paths[i] = fp;
                     i += 1;
                     node = &self.tree;
@@ -58,8 +93,17 @@ impl FieldReader {
             }
         }
 
-        paths[..i]
-            .iter_mut()
-            .for_each(|fp| state.set(fp, serializer.get_decoder_for_field_path(fp).decode(reader)))
+        for fp in paths[..i].iter() {
+            let decoder = serializer
+                .get_decoder_for_field_path(fp)
+                .ok_or_else(|| SerializerError::InvalidFieldPath(fp.to_string()))?;
+            visit(fp, decoder, reader);
+        }
+
+        if reader.is_overrun() {
+            return Err(SerializerError::InvalidFieldPath(fp.to_string()));
+        }
+
+        Ok(i)
     }
 }
@@ -53,7 +53,7 @@ impl FieldOp {
             FieldOp::PlusTwo => fp.inc_curr(2),
             FieldOp::PlusThree => fp.inc_curr(3),
             FieldOp::PlusFour => fp.inc_curr(4),
-            FieldOp::PlusN => fp.inc_curr(r.read_ubit_var_fp_no_refill() as u16 + 5),
+            FieldOp::PlusN => fp.inc_curr((r.read_ubit_var_fp_no_refill() as u16).wrapping_add(5)),
             FieldOp::PushOneLeftDeltaZeroRightZero => fp.push(0),
             FieldOp::PushOneLeftDeltaZeroRightNonZero => {
                 fp.push(r.read_ubit_var_fp_no_refill() as u16);
@@ -71,8 +71,8 @@ impl FieldOp {
                 fp.push(0);
             }
             FieldOp::PushOneLeftDeltaNRightNonZero => {
-                fp.inc_curr(r.read_ubit_var_fp_no_refill() as u16 + 2);
-                fp.push(r.read_ubit_var_fp_no_refill() as u16 + 1);
+                fp.inc_curr((r.read_ubit_var_fp_no_refill() as u16).wrapping_add(2));
+                fp.push((r.read_ubit_var_fp_no_refill() as u16).wrapping_add(1));
             }
             FieldOp::PushOneLeftDeltaNRightNonZeroPack6Bits => {
                 fp.inc_curr(r.read_bits_no_refill(3) as u16 + 2);
@@ -123,42 +123,42 @@ impl FieldOp {
                 fp.push(r.read_bits_no_refill(5) as u16);
             }
             FieldOp::PushTwoLeftDeltaN => {
-                fp.inc_curr(r.read_ubit_var() as u16 + 2);
+                fp.inc_curr((r.read_ubit_var() as u16).wrapping_add(2));
                 fp.push(r.read_ubit_var_fp_no_refill() as u16);
                 fp.push(r.read_ubit_var_fp_no_refill() as u16);
             }
             FieldOp::PushTwoPack5LeftDeltaN => {
-                fp.inc_curr(r.read_ubit_var() as u16 + 2);
+                fp.inc_curr((r.read_ubit_var() as u16).wrapping_add(2));
                 fp.push(r.read_bits_no_refill(5) as u16);
                 fp.push(r.read_bits_no_refill(5) as u16);
             }
             FieldOp::PushThreeLeftDeltaN => {
-                fp.inc_curr(r.read_ubit_var() as u16 + 2);
+                fp.inc_curr((r.read_ubit_var() as u16).wrapping_add(2));
                 fp.push(r.read_ubit_var_fp_no_refill() as u16);
                 fp.push(r.read_ubit_var_fp_no_refill() as u16);
                 fp.push(r.read_ubit_var_fp_no_refill() as u16);
             }
             FieldOp::PushThreePack5LeftDeltaN => {
-                fp.inc_curr(r.read_ubit_var() as u16 + 2);
+                fp.inc_curr((r.read_ubit_var() as u16).wrapping_add(2));
                 fp.push(r.read_bits_no_refill(5) as u16);
                 fp.push(r.read_bits_no_refill(5) as u16);
                 fp.push(r.read_bits_no_refill(5) as u16);
             }
             FieldOp::PushN => {
-                let n = r.read_ubit_var() as i32;
+                let n = r.read_ubit_var() as usize;
                 fp.inc_curr(r.read_ubit_var() as u16);
-                for _ in 0..n {
+                for _ in 0..n.min(fp.path.len()) {
                     fp.push(r.read_ubit_var_fp() as u16)
                 }
             }
             FieldOp::PushNAndNonTopological => {
                 for i in 0..=fp.last {
                     if r.read_bool() {
-                        fp.inc(i, r.read_var_i32() as u16 + 1);
+                        fp.inc(i, (r.read_var_i32() as u16).wrapping_add(1));
                     }
                 }
                 let count = r.read_ubit_var() as usize;
-                for _ in 0..count {
+                for _ in 0..count.min(fp.path.len()) {
                     fp.push(r.read_ubit_var_fp() as u16)
                 }
             }
@@ -169,7 +169,7 @@ impl FieldOp {
 
             FieldOp::PopOnePlusN => {
                 fp.pop(1);
-                fp.inc_curr(r.read_ubit_var_fp_no_refill() as u16 + 1);
+                fp.inc_curr((r.read_ubit_var_fp_no_refill() as u16).wrapping_add(1));
             }
             FieldOp::PopAllButOnePlusOne => {
                 fp.pop(fp.last);
@@ -177,7 +177,7 @@ impl FieldOp {
             }
             FieldOp::PopAllButOnePlusN => {
                 fp.pop(fp.last);
-                fp.inc(0, r.read_ubit_var_fp_no_refill() as u16 + 1);
+                fp.inc(0, (r.read_ubit_var_fp_no_refill() as u16).wrapping_add(1));
             }
             FieldOp::PopAllButOnePlusNPack3Bits => {
                 fp.pop(fp.last);
@@ -211,7 +211,7 @@ impl FieldOp {
                 }
             }
             FieldOp::NonTopoPenultimatePlusOne => {
-                fp.inc(fp.last - 1, 1);
+                fp.inc(fp.last.wrapping_sub(1), 1);
             }
             FieldOp::NonTopoComplexPack4Bits => {
                 for i in 0..=fp.last {
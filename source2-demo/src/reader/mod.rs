@@ -1,10 +1,12 @@
 mod bits;
 mod field;
 mod msg;
+mod stream;
 
 pub(crate) use bits::*;
 pub(crate) use field::*;
 pub(crate) use msg::*;
+pub(crate) use stream::*;
 
 use bitter::{BitReader, LittleEndianReader};
 
@@ -12,6 +14,7 @@ pub struct Reader<'a> {
     pub buf: &'a [u8],
     pub le_reader: LittleEndianReader<'a>,
     pub(crate) string_buf: [u8; 4096],
+    pub(crate) overrun: bool,
 }
 
 impl<'a> Reader<'a> {
@@ -20,12 +23,34 @@ impl<'a> Reader<'a> {
             buf,
             le_reader: LittleEndianReader::new(buf),
             string_buf: [0; 4096],
+            overrun: false,
         }
     }
 
     pub fn reset_to(&mut self, offset: usize) {
         assert!(offset <= self.buf.len());
-        self.le_reader = LittleEndianReader::new(&self.buf[offset..])
+        self.le_reader = LittleEndianReader::new(&self.buf[offset..]);
+        self.overrun = false;
+    }
+
+    /// Returns `true` if any read went past the end of the buffer. Values
+    /// returned by such reads are zeroed.
+    #[inline]
+    pub fn is_overrun(&self) -> bool {
+        self.overrun
+    }
+
+    /// Byte offset of the next unread byte. Meaningful only on byte
+    /// boundaries, i.e. between outer messages.
+    #[inline]
+    pub fn position(&mut self) -> usize {
+        self.buf.len() - self.bytes_remaining()
+    }
+
+    /// Bit offset of the next unread bit.
+    #[inline]
+    pub fn bit_position(&self) -> usize {
+        self.buf.len() * 8 - self.le_reader.bits_remaining().unwrap_or(0)
     }
 
     #[inline]
@@ -3,11 +3,13 @@ use crate::error::ParserError;
 use crate::proto::{
     CCitadelUserMsgPostMatchDetails, CDemoPacket, CMsgMatchMetaDataContents, CitadelUserMessageIds,
 };
+use crate::parser::{Keyframe, KeyframeIndex};
 use crate::proto::{CDemoFileInfo, EDemoCommands, Message};
 use crate::reader::bits::BitsReader;
-use crate::reader::Reader;
+use crate::reader::{DynRead, Reader, StreamReader};
 
 pub(crate) struct OuterMessage {
+    pub(crate) offset: usize,
     pub(crate) msg_type: EDemoCommands,
     pub(crate) tick: u32,
     pub(crate) buf: Vec<u8>,
@@ -18,6 +20,8 @@ pub(crate) trait MessageReader {
 
     fn read_replay_info(&mut self) -> Result<CDemoFileInfo, ParserError>;
 
+    fn read_keyframe_index(&mut self) -> Result<KeyframeIndex, ParserError>;
+
     #[cfg(feature = "deadlock")]
     fn read_deadlock_match_details(&mut self) -> Result<CMsgMatchMetaDataContents, ParserError>;
 }
@@ -29,23 +33,29 @@ impl MessageReader for Reader<'_> {
             return Ok(None);
         }
 
+        let offset = self.position();
         let cmd = self.read_var_u32() as i32;
         let tick = self.read_var_u32();
         let size = self.read_var_u32();
 
-        let msg_type =
-            EDemoCommands::try_from(cmd & !(EDemoCommands::DemIsCompressed as i32)).unwrap();
+        let msg_type = EDemoCommands::try_from(cmd & !(EDemoCommands::DemIsCompressed as i32))
+            .map_err(|_| ParserError::UnknownDemoCommand(cmd))?;
         let msg_compressed = cmd & EDemoCommands::DemIsCompressed as i32 != 0;
 
+        let buf = self.read_bytes(size);
+        if self.is_overrun() {
+            return Err(std::io::Error::from(std::io::ErrorKind::UnexpectedEof).into());
+        }
+
         let buf = if msg_compressed {
-            let buf = self.read_bytes(size);
             let mut decoder = snap::raw::Decoder::new();
             decoder.decompress_vec(&buf)?
         } else {
-            self.read_bytes(size)
+            buf
         };
 
         Ok(Some(OuterMessage {
+            offset,
             msg_type,
             tick,
             buf,
@@ -60,9 +70,40 @@ impl MessageReader for Reader<'_> {
         }
 
         let mut reader = Reader::new(&self.buf[offset..]);
-        Ok(CDemoFileInfo::decode(
-            reader.read_next_message()?.unwrap().buf.as_slice(),
-        )?)
+        let message = reader
+            .read_next_message()?
+            .ok_or(ParserError::ReplayEncodingError)?;
+        Ok(CDemoFileInfo::decode(message.buf.as_slice())?)
+    }
+
+    fn read_keyframe_index(&mut self) -> Result<KeyframeIndex, ParserError> {
+        let mut temp_reader = Reader::new(self.buf);
+        temp_reader.reset_to(16);
+
+        let mut index = KeyframeIndex::default();
+        while temp_reader.bytes_remaining() != 0 {
+            let offset = temp_reader.position();
+            let cmd = temp_reader.read_var_u32() as i32;
+            let tick = temp_reader.read_var_u32();
+            let size = temp_reader.read_var_u32() as usize;
+
+            // Truncated replay, index messages that were written completely.
+            let next = temp_reader.position() + size;
+            if temp_reader.is_overrun() || next > self.buf.len() {
+                break;
+            }
+
+            if cmd & !(EDemoCommands::DemIsCompressed as i32) == EDemoCommands::DemFullPacket as i32
+            {
+                index.insert(Keyframe { tick, offset });
+            }
+
+            temp_reader.reset_to(next);
+        }
+        index.complete = temp_reader.bytes_remaining() == 0;
+        index.scanned = true;
+
+        Ok(index)
     }
 
     #[cfg(feature = "deadlock")]
@@ -70,7 +111,7 @@ impl MessageReader for Reader<'_> {
         let mut temp_reader = Reader::new(self.buf);
         temp_reader.reset_to(16);
         while let Some(message) = temp_reader.read_next_message()? {
-            if EDemoCommands::try_from(message.msg_type) != Ok(EDemoCommands::DemPacket) {
+            if message.msg_type != EDemoCommands::DemPacket {
                 continue;
             }
 
@@ -95,3 +136,74 @@ impl MessageReader for Reader<'_> {
         Err(ParserError::MatchDetailsNotFound)
     }
 }
+
+/// Source of outer messages: either the whole replay in memory or a stream
+/// that can only be read forward.
+pub(crate) enum DemoReader<'a> {
+    Slice(Box<Reader<'a>>),
+    Stream(StreamReader<Box<DynRead<'a>>>),
+}
+
+impl DemoReader<'_> {
+    pub(crate) fn is_seekable(&self) -> bool {
+        matches!(self, DemoReader::Slice(_))
+    }
+
+    /// Length of replay in bytes, unknown for streams.
+    pub(crate) fn replay_len(&self) -> Option<usize> {
+        match self {
+            DemoReader::Slice(reader) => Some(reader.buf.len()),
+            DemoReader::Stream(_) => None,
+        }
+    }
+
+    /// Offset of the next outer message.
+    pub(crate) fn position(&mut self) -> usize {
+        match self {
+            DemoReader::Slice(reader) => reader.position(),
+            DemoReader::Stream(reader) => reader.position(),
+        }
+    }
+
+    pub(crate) fn reset_to(&mut self, offset: usize) -> Result<(), ParserError> {
+        match self {
+            DemoReader::Slice(reader) => {
+                reader.reset_to(offset);
+                Ok(())
+            }
+            DemoReader::Stream(_) => Err(ParserError::SeekNotSupported),
+        }
+    }
+}
+
+impl MessageReader for DemoReader<'_> {
+    #[inline]
+    fn read_next_message(&mut self) -> Result<Option<OuterMessage>, ParserError> {
+        match self {
+            DemoReader::Slice(reader) => reader.read_next_message(),
+            DemoReader::Stream(reader) => reader.read_next_message(),
+        }
+    }
+
+    fn read_replay_info(&mut self) -> Result<CDemoFileInfo, ParserError> {
+        match self {
+            DemoReader::Slice(reader) => reader.read_replay_info(),
+            DemoReader::Stream(_) => Err(ParserError::SeekNotSupported),
+        }
+    }
+
+    fn read_keyframe_index(&mut self) -> Result<KeyframeIndex, ParserError> {
+        match self {
+            DemoReader::Slice(reader) => reader.read_keyframe_index(),
+            DemoReader::Stream(_) => Err(ParserError::SeekNotSupported),
+        }
+    }
+
+    #[cfg(feature = "deadlock")]
+    fn read_deadlock_match_details(&mut self) -> Result<CMsgMatchMetaDataContents, ParserError> {
+        match self {
+            DemoReader::Slice(reader) => reader.read_deadlock_match_details(),
+            DemoReader::Stream(_) => Err(ParserError::SeekNotSupported),
+        }
+    }
+}
@@ -0,0 +1,115 @@
+use crate::error::ParserError;
+use crate::proto::EDemoCommands;
+use crate::reader::OuterMessage;
+use std::io::{BufReader, Read};
+
+#[cfg(not(feature = "send"))]
+pub(crate) type DynRead<'a> = dyn Read + 'a;
+
+#[cfg(feature = "send")]
+pub(crate) type DynRead<'a> = dyn Read + Send + 'a;
+
+/// Reads outer messages one by one from any [`Read`] source. Only the
+/// message being decoded is kept in memory.
+pub(crate) struct StreamReader<R: Read> {
+    inner: BufReader<R>,
+    offset: usize,
+}
+
+impl<R: Read> StreamReader<R> {
+    pub(crate) fn new(inner: R) -> Self {
+        StreamReader {
+            inner: BufReader::new(inner),
+            offset: 0,
+        }
+    }
+
+    pub(crate) fn read_header(&mut self) -> Result<[u8; 16], ParserError> {
+        let mut header = [0; 16];
+        self.inner
+            .read_exact(&mut header)
+            .map_err(|_| ParserError::WrongMagic)?;
+        self.offset += header.len();
+        Ok(header)
+    }
+
+    pub(crate) fn position(&self) -> usize {
+        self.offset
+    }
+
+    fn read_byte(&mut self) -> Result<Option<u8>, ParserError> {
+        let mut byte = [0; 1];
+        loop {
+            match self.inner.read(&mut byte) {
+                Ok(0) => return Ok(None),
+                Ok(_) => {
+                    self.offset += 1;
+                    return Ok(Some(byte[0]));
+                }
+                Err(e) if e.kind() == std::io::ErrorKind::Interrupted => continue,
+                Err(e) => return Err(e.into()),
+            }
+        }
+    }
+
+    /// Returns `None` if the stream ended before the first byte.
+    fn read_var_u32(&mut self) -> Result<Option<u32>, ParserError> {
+        let mut x: u32 = 0;
+        let mut y: u32 = 0;
+        loop {
+            let Some(byte) = self.read_byte()? else {
+                if y == 0 {
+                    return Ok(None);
+                }
+                return Err(std::io::Error::from(std::io::ErrorKind::UnexpectedEof).into());
+            };
+
+            x |= (byte as u32 & 0x7F) << y;
+            y += 7;
+
+            if (byte & 0x80) == 0 || y == 35 {
+                return Ok(Some(x));
+            }
+        }
+    }
+
+    fn read_var_u32_required(&mut self) -> Result<u32, ParserError> {
+        self.read_var_u32()?
+            .ok_or_else(|| std::io::Error::from(std::io::ErrorKind::UnexpectedEof).into())
+    }
+
+    pub(crate) fn read_next_message(&mut self) -> Result<Option<OuterMessage>, ParserError> {
+        let offset = self.offset;
+        let Some(cmd) = self.read_var_u32()? else {
+            return Ok(None);
+        };
+        let cmd = cmd as i32;
+        let tick = self.read_var_u32_required()?;
+        let size = self.read_var_u32_required()?;
+
+        let msg_type = EDemoCommands::try_from(cmd & !(EDemoCommands::DemIsCompressed as i32))
+            .map_err(|_| ParserError::UnknownDemoCommand(cmd))?;
+        let msg_compressed = cmd & EDemoCommands::DemIsCompressed as i32 != 0;
+
+        let mut buf = vec![];
+        (&mut self.inner).take(size as u64).read_to_end(&mut buf)?;
+        self.offset += buf.len();
+        if buf.len() != size as usize {
+            return Err(std::io::Error::from(std::io::ErrorKind::UnexpectedEof).into());
+        }
+
+        let buf = if msg_compressed {
+            let mut decoder = snap::raw::Decoder::new();
+            decoder.decompress_vec(&buf)?
+        } else {
+            buf
+        };
+
+        Ok(Some(OuterMessage {
+            offset,
+            msg_type,
+            tick,
+            buf,
+        }))
+    }
+}
@@ -7,8 +7,8 @@ pub use row::*;
 use crate::entity::BaselineContainer;
 use crate::error::StringTableError;
 use crate::reader::{BitsReader, Reader};
-use std::cell::RefCell;
-use std::rc::Rc;
+use std::ops::Range;
+use std::sync::Arc;
 
 #[derive(Clone, Default)]
 pub struct StringTable {
@@ -19,7 +19,7 @@ pub struct StringTable {
     pub(crate) user_data_size: i32,
     pub(crate) flags: u32,
     pub(crate) var_int_bit_counts: bool,
-    pub(crate) keys: RefCell<Vec<String>>,
+    pub(crate) keys: Vec<String>,
 }
 
 impl StringTable {
@@ -46,6 +46,71 @@ impl StringTable {
             ))
     }
 
+    /// Reads rows like [`StringTable::parse`] without applying them and
+    /// calls `visit` with bit range of every value, including its size
+    /// prefix, and the decompressed value.
+    pub(crate) fn visit_values(
+        &self,
+        buf: &[u8],
+        num_updates: i32,
+        mut visit: impl FnMut(Range<usize>, Vec<u8>),
+    ) -> Result<(), StringTableError> {
+        let mut reader = Reader::new(buf);
+
+        if self.name == "decalprecache" {
+            return Ok(());
+        }
+
+        for _ in 0..num_updates {
+            reader.refill();
+
+            if !reader.read_bool() {
+                reader.read_var_u32();
+            }
+
+            if reader.read_bool() {
+                if reader.read_bool() {
+                    reader.read_bits_no_refill(10);
+                }
+                reader.read_string();
+            }
+
+            if reader.read_bool() {
+                let start = reader.bit_position();
+                let mut is_compressed = false;
+                let bit_size = if self.user_data_fixed_size {
+                    self.user_data_size as u32
+                } else {
+                    if (self.flags & 0x1) != 0 {
+                        is_compressed = reader.read_bool();
+                    }
+                    if self.var_int_bit_counts {
+                        reader.read_ubit_var() * 8
+                    } else {
+                        reader.read_bits_no_refill(17) * 8
+                    }
+                };
+
+                let value = if is_compressed {
+                    let mut decoder = snap::raw::Decoder::new();
+                    decoder
+                        .decompress_vec(&reader.read_bits_as_bytes(bit_size))
+                        .map_err(|_| StringTableError::CorruptedData(self.name.clone()))?
+                } else {
+                    reader.read_bits_as_bytes(bit_size)
+                };
+
+                visit(start..reader.bit_position(), value);
+            }
+
+            if reader.is_overrun() {
+                return Err(StringTableError::CorruptedData(self.name.clone()));
+            }
+        }
+
+        Ok(())
+    }
+
     pub(crate) fn parse(
         &mut self,
         baselines: &mut BaselineContainer,
@@ -54,9 +119,9 @@ impl StringTable {
     ) -> Result<Vec<i32>, StringTableError> {
         let items = &mut self.items;
         let mut reader = Reader::new(buf);
-        let mut index = -1;
+        let mut index: i32 = -1;
         let mut delta_pos = 0;
-        let mut keys = self.keys.borrow_mut();
+        let keys = &mut self.keys;
 
         let mut modified = vec![];
 
@@ -67,9 +132,9 @@ impl StringTable {
         for _ in 0..num_updates {
             reader.refill();
 
-            index += 1;
+            index = index.wrapping_add(1);
             if !reader.read_bool() {
-                index += reader.read_var_u32() as i32 + 1;
+                index = index.wrapping_add((reader.read_var_u32() as i32).wrapping_add(1));
             }
 
             let key = reader.read_bool().then(|| {
@@ -78,11 +143,9 @@ impl StringTable {
                     let pos = (delta_zero + reader.read_bits_no_refill(5) as usize) & 31;
                     let size = reader.read_bits_no_refill(5) as usize;
 
-                    if delta_pos < pos || keys[pos].len() < size {
-                        reader.read_string()
-                    } else {
-                        let x = String::new();
-                        x + &keys[pos][..size] + &reader.read_string()
+                    match keys[pos].get(..size) {
+                        Some(prefix) if delta_pos >= pos => prefix.to_string() + &reader.read_string(),
+                        _ => reader.read_string(),
                     }
                 } else {
                     reader.read_string()
@@ -107,21 +170,30 @@ impl StringTable {
                     }
                 };
 
-                let value = Rc::new(if is_compressed {
+                let value = Arc::new(if is_compressed {
                     let mut decoder = snap::raw::Decoder::new();
                     decoder
                         .decompress_vec(&reader.read_bits_as_bytes(bit_size))
-                        .unwrap()
+                        .map_err(|_| StringTableError::CorruptedData(self.name.clone()))?
                 } else {
                     reader.read_bits_as_bytes(bit_size)
                 });
 
                 if self.name == "instancebaseline" {
-                    baselines.add_baseline(key.as_ref().unwrap().parse().unwrap(), value.clone());
+                    let key = key.as_deref().unwrap_or_default();
+                    let id = key
+                        .parse()
+                        .map_err(|_| StringTableError::InvalidBaselineKey(key.to_string()))?;
+                    baselines.add_baseline(id, value.clone());
                 }
 
-                value
+                Ok(value)
             });
+            let value = value.transpose()?;
+
+            if reader.is_overrun() || index < 0 {
+                return Err(StringTableError::CorruptedData(self.name.clone()));
+            }
 
             if let Some(x) = items.get_mut(index as usize) {
                 if let Some(k) = key {
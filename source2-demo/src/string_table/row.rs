@@ -1,14 +1,14 @@
-use std::rc::Rc;
+use std::sync::Arc;
 
 #[derive(Clone, Default)]
 pub struct StringTableRow {
     pub(crate) index: i32,
     pub(crate) key: String,
-    pub(crate) value: Option<Rc<Vec<u8>>>,
+    pub(crate) value: Option<Arc<Vec<u8>>>,
 }
 
 impl StringTableRow {
-    pub(crate) fn new(index: i32, key: String, value: Option<Rc<Vec<u8>>>) -> Self {
+    pub(crate) fn new(index: i32, key: String, value: Option<Arc<Vec<u8>>>) -> Self {
         StringTableRow { index, key, value }
     }
 